@@ -1,7 +1,5 @@
 use bevy_math::Vec3;
-use block_mesh_pop::{
-    greedy_quads, ChunkShape, MergeVoxel, MeshVoxel, PopBuffer, VisitedBuffer, VoxelVisibility,
-};
+use block_mesh_pop::{ChunkShape, MergeVoxel, MeshVoxel, Mesher, VoxelVisibility};
 use criterion::{criterion_group, criterion_main, Criterion};
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -34,21 +32,18 @@ impl MergeVoxel for Voxel {
 
 pub fn empty_mesh(c: &mut Criterion) {
     let voxels = [Voxel::EMPTY; 66 * 66 * 66];
-    let mut buffer = PopBuffer::new();
-    let mut visited = VisitedBuffer::new(voxels.len());
+    let mut mesher = Mesher::<66, 66, 66, 1>::new();
 
     c.bench_function("greedy empty mesh", |b| {
-        buffer.reset();
         b.iter(|| {
-            greedy_quads::<66, 66, 66, 1, _>(&voxels, &mut visited, &mut buffer);
+            mesher.mesh_greedy(&voxels);
         })
     });
 }
 
 pub fn sphere_mesh(c: &mut Criterion) {
     let mut voxels = [Voxel::EMPTY; 66 * 66 * 66];
-    let mut buffer = PopBuffer::new();
-    let mut visited = VisitedBuffer::new(voxels.len());
+    let mut mesher = Mesher::<66, 66, 66, 1>::new();
 
     for i in 0..voxels.len() {
         let position = ChunkShape::<66, 66, 66>::delinearize(i as u32);
@@ -61,17 +56,15 @@ pub fn sphere_mesh(c: &mut Criterion) {
     }
 
     c.bench_function("greedy sphere mesh", |b| {
-        buffer.reset();
         b.iter(|| {
-            greedy_quads::<66, 66, 66, 1, _>(&voxels, &mut visited, &mut buffer);
+            mesher.mesh_greedy(&voxels);
         })
     });
 }
 
 pub fn sphere_mesh_lod(c: &mut Criterion) {
     let mut voxels = [Voxel::EMPTY; 66 * 66 * 66];
-    let mut buffer = PopBuffer::new();
-    let mut visited = VisitedBuffer::new(voxels.len());
+    let mut mesher = Mesher::<66, 66, 66, 6>::new();
 
     for i in 0..voxels.len() {
         let position = ChunkShape::<66, 66, 66>::delinearize(i as u32);
@@ -83,30 +76,26 @@ pub fn sphere_mesh_lod(c: &mut Criterion) {
         }
     }
     c.bench_function("greedy sphere mesh lod", |b| {
-        buffer.reset();
         b.iter(|| {
-            greedy_quads::<66, 66, 66, 6, _>(&voxels, &mut visited, &mut buffer);
+            mesher.mesh_greedy(&voxels);
         })
     });
 }
 
 pub fn empty_mesh_small(c: &mut Criterion) {
     let voxels = [Voxel::EMPTY; 18 * 18 * 18];
-    let mut visited = VisitedBuffer::new(voxels.len());
-    let mut buffer = PopBuffer::new();
+    let mut mesher = Mesher::<18, 18, 18, 1>::new();
 
     c.bench_function("greedy empty mesh small", |b| {
-        buffer.reset();
         b.iter(|| {
-            greedy_quads::<18, 18, 18, 1, _>(&voxels, &mut visited, &mut buffer);
+            mesher.mesh_greedy(&voxels);
         })
     });
 }
 
 pub fn sphere_mesh_small(c: &mut Criterion) {
     let mut voxels = [Voxel::EMPTY; 18 * 18 * 18];
-    let mut visited = VisitedBuffer::new(voxels.len());
-    let mut buffer = PopBuffer::new();
+    let mut mesher = Mesher::<18, 18, 18, 1>::new();
 
     for i in 0..voxels.len() {
         let position = ChunkShape::<18, 18, 18>::delinearize(i as u32);
@@ -118,17 +107,15 @@ pub fn sphere_mesh_small(c: &mut Criterion) {
         }
     }
     c.bench_function("greedy sphere mesh small", |b| {
-        buffer.reset();
         b.iter(|| {
-            greedy_quads::<18, 18, 18, 1, _>(&voxels, &mut visited, &mut buffer);
+            mesher.mesh_greedy(&voxels);
         })
     });
 }
 
 pub fn sphere_mesh_lod_small(c: &mut Criterion) {
     let mut voxels = [Voxel::EMPTY; 18 * 18 * 18];
-    let mut visited = VisitedBuffer::new(voxels.len());
-    let mut buffer = PopBuffer::new();
+    let mut mesher = Mesher::<18, 18, 18, 4>::new();
 
     for i in 0..voxels.len() {
         let position = ChunkShape::<18, 18, 18>::delinearize(i as u32);
@@ -141,9 +128,8 @@ pub fn sphere_mesh_lod_small(c: &mut Criterion) {
     }
 
     c.bench_function("greedy sphere mesh lod small", |b| {
-        buffer.reset();
         b.iter(|| {
-            greedy_quads::<18, 18, 18, 4, _>(&voxels, &mut visited, &mut buffer);
+            mesher.mesh_greedy(&voxels);
         })
     });
 }
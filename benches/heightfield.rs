@@ -0,0 +1,60 @@
+use block_mesh_pop::{ChunkShape, MeshVoxel, Mesher, VoxelVisibility};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Voxel {
+    EMPTY,
+    FULL,
+}
+
+impl MeshVoxel for Voxel {
+    fn get_visibility(&self) -> VoxelVisibility {
+        match self {
+            Self::EMPTY => VoxelVisibility::Empty,
+            Self::FULL => VoxelVisibility::Opaque,
+        }
+    }
+}
+
+fn hills() -> Vec<u32> {
+    (0..66 * 66)
+        .map(|i| {
+            let (x, z) = ((i % 66) as f32, (i / 66) as f32);
+            (33.0 + 16.0 * (x / 8.0).sin() * (z / 11.0).cos()) as u32
+        })
+        .collect()
+}
+
+pub fn hills_mesh_lod(c: &mut Criterion) {
+    let heights = hills();
+    let mut mesher = Mesher::<66, 66, 66, 6>::new();
+
+    c.bench_function("heightfield hills mesh lod", |b| {
+        b.iter(|| {
+            mesher.mesh_heightfield(&heights);
+        })
+    });
+}
+
+pub fn hills_mesh_lod_visible_faces(c: &mut Criterion) {
+    let heights = hills();
+    let mut voxels = [Voxel::EMPTY; 66 * 66 * 66];
+    let mut mesher = Mesher::<66, 66, 66, 6>::new();
+
+    for i in 0..voxels.len() {
+        let position = ChunkShape::<66, 66, 66>::delinearize(i as u32);
+
+        if position.y < heights[(position.x + position.z * 66) as usize] {
+            voxels[i] = Voxel::FULL;
+        }
+    }
+
+    c.bench_function("heightfield hills mesh lod visible faces", |b| {
+        b.iter(|| {
+            mesher.mesh_visible_faces(&voxels);
+        })
+    });
+}
+
+criterion_group!(benches, hills_mesh_lod, hills_mesh_lod_visible_faces);
+criterion_main!(benches);
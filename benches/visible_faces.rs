@@ -1,7 +1,5 @@
 use bevy_math::Vec3;
-use block_mesh_pop::{
-    visible_faces_quads, ChunkShape, MeshVoxel, PopBuffer, VisitedBuffer, VoxelVisibility,
-};
+use block_mesh_pop::{ChunkShape, MeshVoxel, Mesher, VoxelVisibility};
 use criterion::{criterion_group, criterion_main, Criterion};
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -21,21 +19,18 @@ impl MeshVoxel for Voxel {
 
 pub fn empty_mesh(c: &mut Criterion) {
     let voxels = [Voxel::EMPTY; 66 * 66 * 66];
-    let mut visited = VisitedBuffer::new(voxels.len());
-    let mut buffer = PopBuffer::new();
+    let mut mesher = Mesher::<66, 66, 66, 1>::new();
 
     c.bench_function("visible faces empty mesh", |b| {
-        buffer.reset();
         b.iter(|| {
-            visible_faces_quads::<66, 66, 66, 1, _>(&voxels, &mut visited, &mut buffer);
+            mesher.mesh_visible_faces(&voxels);
         })
     });
 }
 
 pub fn sphere_mesh(c: &mut Criterion) {
     let mut voxels = [Voxel::EMPTY; 66 * 66 * 66];
-    let mut visited = VisitedBuffer::new(voxels.len());
-    let mut buffer = PopBuffer::new();
+    let mut mesher = Mesher::<66, 66, 66, 1>::new();
 
     for i in 0..voxels.len() {
         let position = ChunkShape::<66, 66, 66>::delinearize(i as u32);
@@ -48,17 +43,15 @@ pub fn sphere_mesh(c: &mut Criterion) {
     }
 
     c.bench_function("visible faces sphere mesh", |b| {
-        buffer.reset();
         b.iter(|| {
-            visible_faces_quads::<66, 66, 66, 1, _>(&voxels, &mut visited, &mut buffer);
+            mesher.mesh_visible_faces(&voxels);
         })
     });
 }
 
 pub fn sphere_mesh_lod(c: &mut Criterion) {
     let mut voxels = [Voxel::EMPTY; 66 * 66 * 66];
-    let mut visited = VisitedBuffer::new(voxels.len());
-    let mut buffer = PopBuffer::new();
+    let mut mesher = Mesher::<66, 66, 66, 6>::new();
 
     for i in 0..voxels.len() {
         let position = ChunkShape::<66, 66, 66>::delinearize(i as u32);
@@ -71,30 +64,26 @@ pub fn sphere_mesh_lod(c: &mut Criterion) {
     }
 
     c.bench_function("visible faces sphere mesh lod", |b| {
-        buffer.reset();
         b.iter(|| {
-            visible_faces_quads::<66, 66, 66, 6, _>(&voxels, &mut visited, &mut buffer);
+            mesher.mesh_visible_faces(&voxels);
         })
     });
 }
 
 pub fn empty_mesh_small(c: &mut Criterion) {
     let voxels = [Voxel::EMPTY; 18 * 18 * 18];
-    let mut visited = VisitedBuffer::new(voxels.len());
-    let mut buffer = PopBuffer::new();
+    let mut mesher = Mesher::<18, 18, 18, 1>::new();
 
     c.bench_function("visible faces empty mesh small", |b| {
-        buffer.reset();
         b.iter(|| {
-            visible_faces_quads::<18, 18, 18, 1, _>(&voxels, &mut visited, &mut buffer);
+            mesher.mesh_visible_faces(&voxels);
         })
     });
 }
 
 pub fn sphere_mesh_small(c: &mut Criterion) {
     let mut voxels = [Voxel::EMPTY; 18 * 18 * 18];
-    let mut visited = VisitedBuffer::new(voxels.len());
-    let mut buffer = PopBuffer::new();
+    let mut mesher = Mesher::<18, 18, 18, 1>::new();
 
     for i in 0..voxels.len() {
         let position = ChunkShape::<18, 18, 18>::delinearize(i as u32);
@@ -107,17 +96,15 @@ pub fn sphere_mesh_small(c: &mut Criterion) {
     }
 
     c.bench_function("visible faces sphere mesh small", |b| {
-        buffer.reset();
         b.iter(|| {
-            visible_faces_quads::<18, 18, 18, 1, _>(&voxels, &mut visited, &mut buffer);
+            mesher.mesh_visible_faces(&voxels);
         })
     });
 }
 
 pub fn sphere_mesh_lod_small(c: &mut Criterion) {
     let mut voxels = [Voxel::EMPTY; 18 * 18 * 18];
-    let mut visited = VisitedBuffer::new(voxels.len());
-    let mut buffer = PopBuffer::new();
+    let mut mesher = Mesher::<18, 18, 18, 4>::new();
 
     for i in 0..voxels.len() {
         let position = ChunkShape::<18, 18, 18>::delinearize(i as u32);
@@ -130,9 +117,8 @@ pub fn sphere_mesh_lod_small(c: &mut Criterion) {
     }
 
     c.bench_function("visible faces sphere mesh lod small", |b| {
-        buffer.reset();
         b.iter(|| {
-            visible_faces_quads::<18, 18, 18, 4, _>(&voxels, &mut visited, &mut buffer);
+            mesher.mesh_visible_faces(&voxels);
         })
     });
 }
@@ -9,8 +9,9 @@ use bevy_dolly::{
     system::Dolly,
 };
 use block_mesh_pop::{
-    visible_faces_quads, ChunkShape, LodEasing, LodMaterial, LodMaterialPlugin, LodRenderPlugin,
-    MeshVoxel, PopBuffer, VisitedBuffer, VoxelVisibility, WrappedMaterial,
+    visible_faces_quads, ChunkShape, LodDepthOffset, LodEasing, LodMaterial, LodMaterialPlugin,
+    LodRenderPlugin, LodTransition, MeshVoxel, PopBuffer, VisitedBuffer, VoxelVisibility,
+    WrappedMaterial,
 };
 
 const CHUNK_SIZE: u32 = 32;
@@ -59,7 +60,7 @@ fn setup(
     ));
 
     let voxels = generate_voxels();
-    let (buckets, mesh) = generate_visible_faces_mesh(&voxels);
+    let (face_counts, mesh) = generate_visible_faces_mesh(&voxels);
 
     commands.spawn((
         meshes.add(mesh),
@@ -69,7 +70,14 @@ fn setup(
             max_lod: MAX_LOD as u32,
             period: CHUNK_SIZE * 8,
             easing: LodEasing::Sine,
-            buckets: unsafe { std::mem::transmute(buckets) },
+            face_counts,
+            pixel_tolerance: 1.0,
+            lod_distances: [0.0; MAX_LOD],
+            transition: LodTransition {
+                width: 0.25,
+                depth_offset_scale: 0.02,
+            },
+            depth_offset: LodDepthOffset::Nudge,
         }),
         WrappedMaterial(materials.add(StandardMaterial::from(Color::WHITE))),
     ));
@@ -92,7 +100,7 @@ fn generate_voxels() -> [Voxel; ARRAY_SIZE] {
     voxels
 }
 
-fn generate_visible_faces_mesh(voxels: &[Voxel]) -> ([u32; 8], Mesh) {
+fn generate_visible_faces_mesh(voxels: &[Voxel]) -> ([[u32; 6]; MAX_LOD], Mesh) {
     let mut visited = VisitedBuffer::new(voxels.len());
     let mut buffer = PopBuffer::new();
 
@@ -102,7 +110,7 @@ fn generate_visible_faces_mesh(voxels: &[Voxel]) -> ([u32; 8], Mesh) {
         &mut buffer,
     );
 
-    let buckets = buffer.get_buckets();
+    let face_counts = buffer.get_face_counts();
 
     let num_quads = buffer.num_quads();
 
@@ -129,7 +137,7 @@ fn generate_visible_faces_mesh(voxels: &[Voxel]) -> ([u32; 8], Mesh) {
     mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![Vec2::ZERO; num_vertices]);
     mesh.set_indices(Some(Indices::U32(indices)));
 
-    (buckets, mesh)
+    (face_counts, mesh)
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -1,5 +1,8 @@
+use std::collections::HashSet;
+
 use crate::geometry::{face::OrientedBlockFace, quad::UnorientedQuad};
 
+#[derive(Debug, PartialEq, Eq)]
 pub struct PopBuffer<const M: usize, Q: Into<UnorientedQuad>> {
     pub(crate) groups: [QuadBuffer<Q>; M],
 }
@@ -46,8 +49,8 @@ impl<const M: usize, Q: Into<UnorientedQuad> + Clone> PopBuffer<M, Q> {
     }
 
     #[inline]
-    pub fn get_buckets(&self) -> [u32; 8] {
-        let mut buckets = [0; 8];
+    pub fn get_buckets(&self) -> [u32; M] {
+        let mut buckets = [0; M];
 
         for (max_lod, group) in self.groups.iter().enumerate() {
             for lod in 0..=max_lod {
@@ -58,6 +61,68 @@ impl<const M: usize, Q: Into<UnorientedQuad> + Clone> PopBuffer<M, Q> {
         buckets
     }
 
+    /// The number of quads added at each LOD for each of the [`OrientedBlockFace::FACES`].
+    ///
+    /// Within the quads emitted by [`iter_quads`](Self::iter_quads), these are the lengths of
+    /// consecutive ranges, starting from the last LOD.
+    #[inline]
+    pub fn get_face_counts(&self) -> [[u32; 6]; M] {
+        let mut face_counts = [[0; 6]; M];
+
+        for (lod, group) in self.groups.iter().enumerate() {
+            for (face, quads) in group.groups.iter().enumerate() {
+                face_counts[lod][face] = quads.len() as u32;
+            }
+        }
+
+        face_counts
+    }
+
+    /// The quads drawn at `lod`, snapped to it as they are by the shader.
+    ///
+    /// Quads that snap onto the same quad are only kept once, so the result can be meshed at
+    /// `lod` without the LOD shader. Quads that only partly overlap once snapped are all kept, so
+    /// coplanar faces may still overlap there.
+    pub fn to_lod_quads(&self, lod: usize) -> QuadBuffer<UnorientedQuad> {
+        let mut output = QuadBuffer::new();
+        self.snap_quads(lod, &mut |face_index: usize, quad, _| {
+            output.groups[face_index].push(quad)
+        });
+        output
+    }
+
+    /// The quads of the LODs from `lod` up, snapped to `lod` as by
+    /// [`to_lod_quads`](Self::to_lod_quads) and kept in their groups.
+    ///
+    /// Snapped quads don't move at finer LODs, so when drawn with
+    /// [`LodMaterial`](crate::LodMaterial) the finer LODs look like `lod`. This lets a buffer
+    /// whose finer LODs haven't been loaded yet be drawn without holes.
+    pub fn snap_to_lod(&self, lod: usize) -> PopBuffer<M, UnorientedQuad> {
+        let mut output = PopBuffer::new();
+        self.snap_quads(lod, &mut output);
+        output
+    }
+
+    /// Adds the quads drawn at `lod` to `output`, snapped to it and without duplicates.
+    ///
+    /// Coarser groups are drawn at every LOD the finer ones are, so only their copy of a quad is
+    /// kept.
+    fn snap_quads(&self, lod: usize, output: &mut impl QuadSink<UnorientedQuad>) {
+        for (face_index, face) in OrientedBlockFace::FACES.iter().enumerate() {
+            let mut seen = HashSet::new();
+
+            for (group_lod, group) in self.groups.iter().enumerate().skip(lod).rev() {
+                for quad in &group.groups[face_index] {
+                    let quad = face.quad_into_lod(quad.clone().into(), lod);
+
+                    if seen.insert(quad) {
+                        output.add_quad(face_index, quad, group_lod);
+                    }
+                }
+            }
+        }
+    }
+
     #[inline]
     pub fn iter_quads(self) -> impl Iterator<Item = (OrientedBlockFace, Q)> {
         self.groups
@@ -66,6 +131,12 @@ impl<const M: usize, Q: Into<UnorientedQuad> + Clone> PopBuffer<M, Q> {
             .flat_map(|group| group.iter_quads())
     }
 
+    /// Like [`iter_quads`](Self::iter_quads), without consuming the buffer.
+    #[inline]
+    pub fn quads(&self) -> impl Iterator<Item = (OrientedBlockFace, &Q)> {
+        self.groups.iter().rev().flat_map(|group| group.quads())
+    }
+
     #[inline]
     pub fn iter_quads_lod(self, lod: usize) -> impl Iterator<Item = (OrientedBlockFace, Q)> {
         self.groups
@@ -113,7 +184,29 @@ impl<const M: usize, Q: Into<UnorientedQuad> + Clone> PopBuffer<M, Q> {
     // }
 }
 
-#[derive(Debug)]
+/// Receives the quads of a mesher, as `(face_index, quad, lod)`.
+///
+/// Implemented by [`PopBuffer`] and by closures, so quads can be counted or written straight
+/// to their destination without storing them.
+pub trait QuadSink<Q> {
+    fn add_quad(&mut self, face_index: usize, quad: Q, lod: usize);
+}
+
+impl<const M: usize, Q: Into<UnorientedQuad> + Clone> QuadSink<Q> for PopBuffer<M, Q> {
+    #[inline]
+    fn add_quad(&mut self, face_index: usize, quad: Q, lod: usize) {
+        PopBuffer::add_quad(self, face_index, quad, lod)
+    }
+}
+
+impl<Q, F: FnMut(usize, Q, usize)> QuadSink<Q> for F {
+    #[inline]
+    fn add_quad(&mut self, face_index: usize, quad: Q, lod: usize) {
+        self(face_index, quad, lod)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
 pub struct QuadBuffer<Q: Into<UnorientedQuad>> {
     pub(crate) groups: [Vec<Q>; 6],
 }
@@ -160,6 +253,14 @@ impl<Q: Into<UnorientedQuad>> QuadBuffer<Q> {
         self.iter_groups()
             .flat_map(|(face, group)| group.into_iter().map(move |quad| (face, quad)))
     }
+
+    #[inline]
+    pub fn quads(&self) -> impl Iterator<Item = (OrientedBlockFace, &Q)> {
+        OrientedBlockFace::FACES
+            .into_iter()
+            .zip(self.groups.iter())
+            .flat_map(|(face, group)| group.iter().map(move |quad| (face, quad)))
+    }
 }
 
 // impl<const M: usize, Q: Into<UnorientedQuad>> From<PopBuffer<M, Q>> for QuadBuffer<Q> {
@@ -173,9 +274,8 @@ impl<Q: Into<UnorientedQuad>> QuadBuffer<Q> {
 
 #[derive(Debug)]
 pub struct VisitedBuffer {
-    // This really should be a smaller type (u8)
-    // but a u32 vec is 2x faster for some reason.
-    pub(crate) visited: Vec<u8>,
+    // One bit per LOD, which is enough for any chunk shape whose volume fits in a u32.
+    pub(crate) visited: Vec<u16>,
 }
 
 impl VisitedBuffer {
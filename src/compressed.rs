@@ -0,0 +1,267 @@
+use bevy_math::UVec3;
+
+use crate::{
+    encoding::{is_valid_quad, read_header, write_header, HEADER_LEN},
+    DecodeError, EncodeQuad, OrientedBlockFace, PopBuffer, UnorientedQuad,
+};
+
+const MAGIC: [u8; 4] = *b"POPC";
+
+/// How [`PopBuffer::encode_compressed`] compresses quads.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Compression {
+    /// Positions are delta-encoded within each face group, and every value is a varint.
+    #[default]
+    Delta,
+    /// [`Delta`](Self::Delta), followed by LZ4.
+    #[cfg(feature = "lz4")]
+    Lz4,
+}
+
+impl Compression {
+    #[inline]
+    fn id(self) -> u8 {
+        match self {
+            Self::Delta => 0,
+            #[cfg(feature = "lz4")]
+            Self::Lz4 => 1,
+        }
+    }
+
+    #[inline]
+    fn from_id(id: u8) -> Result<Self, DecodeError> {
+        match id {
+            0 => Ok(Self::Delta),
+            #[cfg(feature = "lz4")]
+            1 => Ok(Self::Lz4),
+            _ => Err(DecodeError::UnsupportedCompression(id)),
+        }
+    }
+}
+
+impl<const M: usize, Q: EncodeQuad> PopBuffer<M, Q> {
+    /// Encodes the quads meshed from a chunk of `X * Y * Z` voxels more compactly than
+    /// [`encode`](Self::encode), at the cost of speed.
+    ///
+    /// Quads of a face group are emitted in scan order, so each position is stored as the
+    /// difference from the previous one along the axes of the face.
+    pub fn encode_compressed<const X: u32, const Y: u32, const Z: u32>(
+        &self,
+        compression: Compression,
+    ) -> Vec<u8> {
+        let shape = UVec3::new(X, Y, Z);
+
+        let mut payload = Vec::new();
+
+        for counts in self.get_face_counts() {
+            for count in counts {
+                write_varint(&mut payload, count as u64);
+            }
+        }
+
+        for group in self.groups.iter() {
+            for (face, quads) in OrientedBlockFace::FACES.iter().zip(group.groups.iter()) {
+                let mut previous = 0;
+                for quad in quads {
+                    let sizes = quad.sizes();
+                    let quad: UnorientedQuad = quad.clone().into();
+
+                    let index = face_index(face, shape, quad.minimum) as i64;
+                    write_varint(&mut payload, zigzag(index - previous));
+                    previous = index;
+
+                    for size in &sizes[..Q::SIZES] {
+                        write_varint(&mut payload, *size as u64);
+                    }
+                }
+            }
+        }
+
+        let mut bytes = Vec::with_capacity(HEADER_LEN + 1 + payload.len());
+        write_header::<X, Y, Z, M, Q>(&mut bytes, MAGIC);
+        bytes.push(compression.id());
+
+        match compression {
+            Compression::Delta => bytes.extend_from_slice(&payload),
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => {
+                bytes.extend_from_slice(&lz4_flex::compress_prepend_size(&payload));
+            }
+        }
+
+        bytes
+    }
+
+    /// Decodes quads written by [`encode_compressed`](Self::encode_compressed) with the same
+    /// chunk shape and any [`Compression`].
+    pub fn decode_compressed<const X: u32, const Y: u32, const Z: u32>(
+        bytes: &[u8],
+    ) -> Result<Self, DecodeError> {
+        let header = bytes
+            .get(..HEADER_LEN + 1)
+            .ok_or(DecodeError::UnexpectedEnd)?;
+        read_header::<X, Y, Z, M, Q>(header, MAGIC)?;
+
+        let payload = &bytes[HEADER_LEN + 1..];
+        let shape = UVec3::new(X, Y, Z);
+
+        #[cfg(feature = "lz4")]
+        let decompressed;
+        let mut payload = match Compression::from_id(header[HEADER_LEN])? {
+            Compression::Delta => payload,
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => {
+                decompressed = decompress_lz4(payload, max_payload_len::<M, Q>(shape))?;
+                &decompressed
+            }
+        };
+
+        let mut counts = [[0; 6]; M];
+        for count in counts.iter_mut().flatten() {
+            *count = read_varint(&mut payload)? as usize;
+        }
+
+        let mut buffer = Self::new();
+
+        for (group, counts) in buffer.groups.iter_mut().zip(counts) {
+            for ((face, quads), count) in OrientedBlockFace::FACES
+                .iter()
+                .zip(group.groups.iter_mut())
+                .zip(counts)
+            {
+                // Every quad takes at least a byte.
+                if count > payload.len() {
+                    return Err(DecodeError::UnexpectedEnd);
+                }
+                quads.reserve_exact(count);
+
+                let mut previous: i64 = 0;
+                for _ in 0..count {
+                    let index = previous.wrapping_add(unzigzag(read_varint(&mut payload)?));
+                    previous = index;
+
+                    let minimum = u32::try_from(index)
+                        .ok()
+                        .and_then(|index| face_position(face, shape, index))
+                        .ok_or(DecodeError::InvalidQuad)?;
+
+                    let mut sizes = [1; 2];
+                    for size in &mut sizes[..Q::SIZES] {
+                        *size = u32::try_from(read_varint(&mut payload)?)
+                            .map_err(|_| DecodeError::InvalidQuad)?;
+                    }
+                    if !is_valid_quad(face, shape, minimum, sizes) {
+                        return Err(DecodeError::InvalidQuad);
+                    }
+
+                    quads.push(Q::from_parts(minimum, sizes));
+                }
+            }
+        }
+
+        if !payload.is_empty() {
+            return Err(DecodeError::TrailingBytes);
+        }
+
+        Ok(buffer)
+    }
+}
+
+/// Decompresses a payload written by `compress_prepend_size`, which a valid payload can't make
+/// larger than `max_len`.
+#[cfg(feature = "lz4")]
+fn decompress_lz4(payload: &[u8], max_len: u64) -> Result<Vec<u8>, DecodeError> {
+    let (len, compressed) =
+        lz4_flex::block::uncompressed_size(payload).map_err(|_| DecodeError::UnexpectedEnd)?;
+    if len as u64 > max_len {
+        return Err(DecodeError::InvalidCompressedData);
+    }
+
+    let mut decompressed = vec![0; len];
+    match lz4_flex::decompress_into(compressed, &mut decompressed) {
+        Ok(written) if written == len => Ok(decompressed),
+        _ => Err(DecodeError::InvalidCompressedData),
+    }
+}
+
+/// The longest payload of a chunk of `shape` with `M` LODs, in which every face of every voxel
+/// is a quad at every LOD.
+#[cfg(feature = "lz4")]
+fn max_payload_len<const M: usize, Q: EncodeQuad>(shape: UVec3) -> u64 {
+    let voxels = (shape.x as u64)
+        .saturating_mul(shape.y as u64)
+        .saturating_mul(shape.z as u64);
+
+    // Deltas between positions are zigzag encoded, doubling them.
+    let quad_len = varint_len(voxels.saturating_mul(2))
+        + Q::SIZES as u64 * varint_len(shape.max_element() as u64);
+    let group_len = varint_len(voxels).saturating_add(voxels.saturating_mul(quad_len));
+
+    group_len.saturating_mul(6 * M as u64)
+}
+
+/// Linearizes `position` along the normal, then the v and u axes of `face`.
+#[inline]
+fn face_index(face: &OrientedBlockFace, shape: UVec3, position: UVec3) -> u32 {
+    let (u_size, v_size) = (face.u.dot(shape), face.v.dot(shape));
+
+    (face.n.dot(position) * v_size + face.v.dot(position)) * u_size + face.u.dot(position)
+}
+
+#[inline]
+fn face_position(face: &OrientedBlockFace, shape: UVec3, index: u32) -> Option<UVec3> {
+    let (u_size, v_size) = (face.u.dot(shape), face.v.dot(shape));
+
+    let u = index % u_size;
+    let v = (index / u_size) % v_size;
+    let n = index / u_size / v_size;
+
+    (n < face.n.dot(shape)).then(|| face.n * n + face.u * u + face.v * v)
+}
+
+#[inline]
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+#[inline]
+fn unzigzag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+#[inline]
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+#[cfg(feature = "lz4")]
+#[inline]
+fn varint_len(value: u64) -> u64 {
+    ((u64::BITS - value.leading_zeros() + 6) / 7).max(1) as u64
+}
+
+#[inline]
+fn read_varint(bytes: &mut &[u8]) -> Result<u64, DecodeError> {
+    let mut value = 0;
+
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = bytes.split_first().ok_or(DecodeError::UnexpectedEnd)?;
+        *bytes = rest;
+
+        // Only the lowest bit of the tenth byte fits.
+        if shift == 63 && byte & 0x7e != 0 {
+            return Err(DecodeError::VarintOverflow);
+        }
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+
+    Err(DecodeError::VarintOverflow)
+}
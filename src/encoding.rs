@@ -0,0 +1,448 @@
+use std::{fmt, io};
+
+use bevy_math::UVec3;
+
+use crate::{
+    OrientedBlockFace, PopBuffer, UnorientedQuad, UnorientedRegularQuad, UnorientedUnitQuad,
+};
+
+const MAGIC: [u8; 4] = *b"POPB";
+const VERSION: u8 = 1;
+pub(crate) const HEADER_LEN: usize = MAGIC.len() + 2 + 3 * 4 + 1;
+
+/// A quad type that can be stored by [`PopBuffer::encode`].
+pub trait EncodeQuad: Into<UnorientedQuad> + Clone {
+    /// Identifies the quad type in the header.
+    const KIND: u8;
+    /// How many of [`sizes`](Self::sizes) are stored after the minimum.
+    const SIZES: usize;
+
+    fn sizes(&self) -> [u32; 2];
+    fn from_parts(minimum: UVec3, sizes: [u32; 2]) -> Self;
+}
+
+impl EncodeQuad for UnorientedUnitQuad {
+    const KIND: u8 = 0;
+    const SIZES: usize = 0;
+
+    #[inline]
+    fn sizes(&self) -> [u32; 2] {
+        [1, 1]
+    }
+
+    #[inline]
+    fn from_parts(minimum: UVec3, _sizes: [u32; 2]) -> Self {
+        Self { minimum }
+    }
+}
+
+impl EncodeQuad for UnorientedRegularQuad {
+    const KIND: u8 = 1;
+    const SIZES: usize = 1;
+
+    #[inline]
+    fn sizes(&self) -> [u32; 2] {
+        [self.size, self.size]
+    }
+
+    #[inline]
+    fn from_parts(minimum: UVec3, sizes: [u32; 2]) -> Self {
+        Self {
+            minimum,
+            size: sizes[0],
+        }
+    }
+}
+
+impl EncodeQuad for UnorientedQuad {
+    const KIND: u8 = 2;
+    const SIZES: usize = 2;
+
+    #[inline]
+    fn sizes(&self) -> [u32; 2] {
+        [self.width, self.height]
+    }
+
+    #[inline]
+    fn from_parts(minimum: UVec3, sizes: [u32; 2]) -> Self {
+        Self {
+            minimum,
+            width: sizes[0],
+            height: sizes[1],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The data doesn't start with the magic bytes of an encoded [`PopBuffer`].
+    InvalidMagic,
+    UnsupportedVersion(u8),
+    /// The quads were encoded as a different quad type.
+    QuadKindMismatch {
+        expected: u8,
+        found: u8,
+    },
+    /// The quads were meshed from a chunk of a different shape.
+    ShapeMismatch {
+        expected: UVec3,
+        found: UVec3,
+    },
+    LodCountMismatch {
+        expected: usize,
+        found: usize,
+    },
+    /// The data ends before all quads were read.
+    UnexpectedEnd,
+    /// There is data left after the last quad.
+    TrailingBytes,
+    /// The data was compressed in a way this build can't decompress.
+    UnsupportedCompression(u8),
+    InvalidCompressedData,
+    /// A varint of compressed data doesn't fit in 64 bits.
+    VarintOverflow,
+    /// A quad lies outside of the chunk.
+    InvalidQuad,
+    /// A LOD holds more quads than the chunk has voxel faces.
+    TooManyQuads,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidMagic => write!(f, "not an encoded pop buffer"),
+            Self::UnsupportedVersion(version) => write!(f, "unsupported version {version}"),
+            Self::QuadKindMismatch { expected, found } => {
+                write!(f, "expected quad kind {expected}, found {found}")
+            }
+            Self::ShapeMismatch { expected, found } => {
+                write!(f, "expected chunk shape {expected}, found {found}")
+            }
+            Self::LodCountMismatch { expected, found } => {
+                write!(f, "expected {expected} LODs, found {found}")
+            }
+            Self::UnexpectedEnd => write!(f, "unexpected end of data"),
+            Self::TrailingBytes => write!(f, "trailing bytes after the last quad"),
+            Self::UnsupportedCompression(id) => write!(f, "unsupported compression {id}"),
+            Self::InvalidCompressedData => write!(f, "invalid compressed data"),
+            Self::VarintOverflow => write!(f, "varint longer than 64 bits"),
+            Self::InvalidQuad => write!(f, "invalid quad"),
+            Self::TooManyQuads => write!(f, "more quads than faces in the chunk"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl From<DecodeError> for io::Error {
+    fn from(error: DecodeError) -> Self {
+        let kind = match error {
+            DecodeError::UnexpectedEnd => io::ErrorKind::UnexpectedEof,
+            _ => io::ErrorKind::InvalidData,
+        };
+        io::Error::new(kind, error)
+    }
+}
+
+impl<const M: usize, Q: EncodeQuad> PopBuffer<M, Q> {
+    /// Encodes the quads meshed from a chunk of `X * Y * Z` voxels.
+    ///
+    /// The header holds the version, quad type, chunk shape and LOD count, followed by the
+    /// number of quads of each LOD and face. Quads are bit-packed with only as many bits as the
+    /// chunk shape needs.
+    pub fn encode<const X: u32, const Y: u32, const Z: u32>(&self) -> Vec<u8> {
+        let bits = QuadBits::new(UVec3::new(X, Y, Z));
+
+        let mut bytes = Vec::with_capacity(
+            HEADER_LEN + M * 6 * 4 + (self.num_quads() * bits.per_quad::<Q>() + 7) / 8,
+        );
+
+        write_header::<X, Y, Z, M, Q>(&mut bytes, MAGIC);
+
+        for counts in self.get_face_counts() {
+            for count in counts {
+                bytes.extend_from_slice(&count.to_le_bytes());
+            }
+        }
+
+        let mut writer = BitWriter::new(bytes);
+        for group in self.groups.iter() {
+            for quads in group.groups.iter() {
+                write_quads(&mut writer, quads, &bits);
+            }
+        }
+
+        writer.finish()
+    }
+
+    /// Decodes quads written by [`encode`](Self::encode) with the same chunk shape.
+    pub fn decode<const X: u32, const Y: u32, const Z: u32>(
+        bytes: &[u8],
+    ) -> Result<Self, DecodeError> {
+        let header = bytes.get(..HEADER_LEN).ok_or(DecodeError::UnexpectedEnd)?;
+        let bits = read_header::<X, Y, Z, M, Q>(header, MAGIC)?;
+
+        let counts_len = M * 6 * 4;
+        let counts = bytes
+            .get(HEADER_LEN..HEADER_LEN + counts_len)
+            .ok_or(DecodeError::UnexpectedEnd)?;
+
+        let mut reader = BitReader::new(&bytes[HEADER_LEN + counts_len..]);
+        let mut buffer = Self::new();
+
+        for (lod, group) in buffer.groups.iter_mut().enumerate() {
+            for (face, quads) in group.groups.iter_mut().enumerate() {
+                let count = read_u32(&counts[(lod * 6 + face) * 4..]) as usize;
+                let face = &OrientedBlockFace::FACES[face];
+                read_quads(&mut reader, face, count, &bits, quads)?;
+            }
+        }
+
+        if !reader.is_done() {
+            return Err(DecodeError::TrailingBytes);
+        }
+
+        Ok(buffer)
+    }
+}
+
+/// Writes the header shared by the encodings of a [`PopBuffer`], starting with `magic`.
+pub(crate) fn write_header<const X: u32, const Y: u32, const Z: u32, const M: usize, Q>(
+    bytes: &mut Vec<u8>,
+    magic: [u8; 4],
+) where
+    Q: EncodeQuad,
+{
+    bytes.extend_from_slice(&magic);
+    bytes.push(VERSION);
+    bytes.push(Q::KIND);
+    for dimension in [X, Y, Z] {
+        bytes.extend_from_slice(&dimension.to_le_bytes());
+    }
+    bytes.push(M as u8);
+}
+
+/// Checks a header written by [`write_header`], returning how quads are packed.
+pub(crate) fn read_header<const X: u32, const Y: u32, const Z: u32, const M: usize, Q>(
+    header: &[u8],
+    magic: [u8; 4],
+) -> Result<QuadBits, DecodeError>
+where
+    Q: EncodeQuad,
+{
+    if header[..4] != magic {
+        return Err(DecodeError::InvalidMagic);
+    }
+    if header[4] != VERSION {
+        return Err(DecodeError::UnsupportedVersion(header[4]));
+    }
+    if header[5] != Q::KIND {
+        return Err(DecodeError::QuadKindMismatch {
+            expected: Q::KIND,
+            found: header[5],
+        });
+    }
+
+    let shape = UVec3::new(X, Y, Z);
+    let found = UVec3::new(
+        read_u32(&header[6..]),
+        read_u32(&header[10..]),
+        read_u32(&header[14..]),
+    );
+    if found != shape {
+        return Err(DecodeError::ShapeMismatch {
+            expected: shape,
+            found,
+        });
+    }
+    if header[18] as usize != M {
+        return Err(DecodeError::LodCountMismatch {
+            expected: M,
+            found: header[18] as usize,
+        });
+    }
+
+    Ok(QuadBits::new(shape))
+}
+
+pub(crate) fn write_quads<Q: EncodeQuad>(writer: &mut BitWriter, quads: &[Q], bits: &QuadBits) {
+    for quad in quads {
+        let sizes = quad.sizes();
+        let quad: UnorientedQuad = quad.clone().into();
+
+        writer.write(quad.minimum.x, bits.position.x);
+        writer.write(quad.minimum.y, bits.position.y);
+        writer.write(quad.minimum.z, bits.position.z);
+        for size in &sizes[..Q::SIZES] {
+            writer.write(*size, bits.size);
+        }
+    }
+}
+
+pub(crate) fn read_quads<Q: EncodeQuad>(
+    reader: &mut BitReader,
+    face: &OrientedBlockFace,
+    count: usize,
+    bits: &QuadBits,
+    quads: &mut Vec<Q>,
+) -> Result<(), DecodeError> {
+    if count > reader.remaining() / bits.per_quad::<Q>().max(1) {
+        return Err(DecodeError::UnexpectedEnd);
+    }
+
+    quads.reserve_exact(count);
+    for _ in 0..count {
+        let minimum = UVec3::new(
+            reader.read(bits.position.x)?,
+            reader.read(bits.position.y)?,
+            reader.read(bits.position.z)?,
+        );
+        let mut sizes = [1; 2];
+        for size in &mut sizes[..Q::SIZES] {
+            *size = reader.read(bits.size)?;
+        }
+
+        if !is_valid_quad(face, bits.shape, minimum, sizes) {
+            return Err(DecodeError::InvalidQuad);
+        }
+        quads.push(Q::from_parts(minimum, sizes));
+    }
+
+    Ok(())
+}
+
+/// Whether a decoded quad on `face` is at least one voxel in size and lies inside a chunk of
+/// `shape`.
+pub(crate) fn is_valid_quad(
+    face: &OrientedBlockFace,
+    shape: UVec3,
+    minimum: UVec3,
+    sizes: [u32; 2],
+) -> bool {
+    let fits = |axis: UVec3, size: u32| {
+        size > 0
+            && axis
+                .dot(minimum)
+                .checked_add(size)
+                .is_some_and(|end| end <= axis.dot(shape))
+    };
+
+    minimum.cmplt(shape).all() && fits(face.u, sizes[0]) && fits(face.v, sizes[1])
+}
+
+#[inline]
+pub(crate) fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+#[inline]
+fn bits_for(value: u32) -> u32 {
+    u32::BITS - value.leading_zeros()
+}
+
+pub(crate) struct QuadBits {
+    shape: UVec3,
+    position: UVec3,
+    size: u32,
+}
+
+impl QuadBits {
+    #[inline]
+    pub(crate) fn new(shape: UVec3) -> Self {
+        Self {
+            shape,
+            position: UVec3::new(bits_for(shape.x), bits_for(shape.y), bits_for(shape.z)),
+            size: bits_for(shape.max_element()),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn per_quad<Q: EncodeQuad>(&self) -> usize {
+        (self.position.x + self.position.y + self.position.z) as usize
+            + self.size as usize * Q::SIZES
+    }
+}
+
+pub(crate) struct BitWriter {
+    bytes: Vec<u8>,
+    scratch: u64,
+    bits: u32,
+}
+
+impl BitWriter {
+    #[inline]
+    pub(crate) fn new(bytes: Vec<u8>) -> Self {
+        Self {
+            bytes,
+            scratch: 0,
+            bits: 0,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn write(&mut self, value: u32, bits: u32) {
+        debug_assert!(bits == 32 || value >> bits == 0);
+
+        self.scratch |= (value as u64) << self.bits;
+        self.bits += bits;
+
+        while self.bits >= 8 {
+            self.bytes.push(self.scratch as u8);
+            self.scratch >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    #[inline]
+    pub(crate) fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.scratch as u8);
+        }
+        self.bytes
+    }
+}
+
+pub(crate) struct BitReader<'a> {
+    bytes: &'a [u8],
+    scratch: u64,
+    bits: u32,
+}
+
+impl<'a> BitReader<'a> {
+    #[inline]
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            scratch: 0,
+            bits: 0,
+        }
+    }
+
+    /// The number of bits left to read.
+    #[inline]
+    pub(crate) fn remaining(&self) -> usize {
+        self.bytes.len() * 8 + self.bits as usize
+    }
+
+    #[inline]
+    pub(crate) fn read(&mut self, bits: u32) -> Result<u32, DecodeError> {
+        while self.bits < bits {
+            let (byte, rest) = self.bytes.split_first().ok_or(DecodeError::UnexpectedEnd)?;
+            self.scratch |= (*byte as u64) << self.bits;
+            self.bits += 8;
+            self.bytes = rest;
+        }
+
+        let value = (self.scratch & ((1 << bits) - 1)) as u32;
+        self.scratch >>= bits;
+        self.bits -= bits;
+
+        Ok(value)
+    }
+
+    /// Whether only the padding of the last byte is left.
+    #[inline]
+    pub(crate) fn is_done(&self) -> bool {
+        self.bytes.is_empty() && self.scratch == 0
+    }
+}
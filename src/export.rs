@@ -0,0 +1,253 @@
+use std::{
+    fmt::Write as _,
+    io::{self, Write},
+};
+
+use bevy_math::Vec3;
+
+use crate::{OrientedBlockFace, PopBuffer, UnorientedQuad};
+
+/// What the exporters write of a [`PopBuffer`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExportOptions {
+    /// The LOD to export, snapped as by [`PopBuffer::to_lod_quads`]. With `None`, every quad is
+    /// exported unsnapped in the order of [`PopBuffer::iter_quads`], so that the mesh can still
+    /// be drawn by [`LodMaterial`](crate::LodMaterial).
+    pub lod: Option<usize>,
+    pub voxel_size: f32,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            lod: None,
+            voxel_size: 1.0,
+        }
+    }
+}
+
+struct ExportMesh {
+    positions: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    indices: Vec<u32>,
+    /// Each LOD with its number of quads, in the order they were added.
+    groups: Vec<(usize, usize)>,
+}
+
+impl ExportMesh {
+    fn new<const M: usize, Q: Into<UnorientedQuad> + Clone>(
+        buffer: &PopBuffer<M, Q>,
+        options: &ExportOptions,
+    ) -> Self {
+        let mut mesh = Self {
+            positions: Vec::new(),
+            normals: Vec::new(),
+            indices: Vec::new(),
+            groups: Vec::new(),
+        };
+
+        match options.lod {
+            Some(lod) => {
+                for (face, quad) in buffer.to_lod_quads(lod).iter_quads() {
+                    mesh.add_quad(face, quad, lod, options.voxel_size);
+                }
+                mesh.groups.push((lod, mesh.indices.len() / 6));
+            }
+            None => {
+                for (lod, group) in buffer.groups.iter().enumerate().rev() {
+                    let start = mesh.indices.len() / 6;
+                    for (face, quad) in group.quads() {
+                        mesh.add_quad(face, quad.clone().into(), 0, options.voxel_size);
+                    }
+                    mesh.groups.push((lod, mesh.indices.len() / 6 - start));
+                }
+            }
+        }
+
+        mesh
+    }
+
+    #[inline]
+    fn add_quad(&mut self, face: OrientedBlockFace, quad: UnorientedQuad, lod: usize, size: f32) {
+        self.indices
+            .extend_from_slice(&face.quad_mesh_indices(self.positions.len() as u32));
+        self.positions
+            .extend_from_slice(&face.quad_mesh_positions(quad, lod, size));
+        self.normals.extend_from_slice(&face.quad_mesh_normals());
+    }
+}
+
+/// Writes `buffer` as a Wavefront OBJ, with a group for each LOD.
+pub fn write_obj<const M: usize, Q: Into<UnorientedQuad> + Clone>(
+    buffer: &PopBuffer<M, Q>,
+    options: &ExportOptions,
+    mut writer: impl Write,
+) -> io::Result<()> {
+    let mesh = ExportMesh::new(buffer, options);
+
+    for position in &mesh.positions {
+        writeln!(writer, "v {} {} {}", position.x, position.y, position.z)?;
+    }
+    for normal in &mesh.normals {
+        writeln!(writer, "vn {} {} {}", normal.x, normal.y, normal.z)?;
+    }
+
+    let mut triangles = mesh.indices.chunks_exact(3);
+    for &(lod, quads) in &mesh.groups {
+        writeln!(writer, "g lod{lod}")?;
+        for triangle in triangles.by_ref().take(quads * 2) {
+            // OBJ indices start at one.
+            let [a, b, c] = [triangle[0] + 1, triangle[1] + 1, triangle[2] + 1];
+            writeln!(writer, "f {a}//{a} {b}//{b} {c}//{c}")?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes `buffer` as a binary PLY.
+pub fn write_ply<const M: usize, Q: Into<UnorientedQuad> + Clone>(
+    buffer: &PopBuffer<M, Q>,
+    options: &ExportOptions,
+    mut writer: impl Write,
+) -> io::Result<()> {
+    let mesh = ExportMesh::new(buffer, options);
+
+    write!(
+        writer,
+        "ply\n\
+         format binary_little_endian 1.0\n\
+         element vertex {}\n\
+         property float x\n\
+         property float y\n\
+         property float z\n\
+         property float nx\n\
+         property float ny\n\
+         property float nz\n\
+         element face {}\n\
+         property list uchar uint vertex_indices\n\
+         end_header\n",
+        mesh.positions.len(),
+        mesh.indices.len() / 3,
+    )?;
+
+    let mut bytes = Vec::with_capacity(mesh.positions.len() * 24 + mesh.indices.len() / 3 * 13);
+    for (position, normal) in mesh.positions.iter().zip(&mesh.normals) {
+        for value in position.to_array().into_iter().chain(normal.to_array()) {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    for triangle in mesh.indices.chunks_exact(3) {
+        bytes.push(3);
+        for index in triangle {
+            bytes.extend_from_slice(&index.to_le_bytes());
+        }
+    }
+
+    writer.write_all(&bytes)
+}
+
+/// Writes `buffer` as a binary glTF of a single mesh.
+///
+/// The extras of its node record the exported `lod`, or with all LODs the `buckets` and
+/// `face_counts` of `buffer`, so that the mesh can be drawn by
+/// [`LodMaterial`](crate::LodMaterial) once loaded.
+pub fn write_glb<const M: usize, Q: Into<UnorientedQuad> + Clone>(
+    buffer: &PopBuffer<M, Q>,
+    options: &ExportOptions,
+    mut writer: impl Write,
+) -> io::Result<()> {
+    const ARRAY_BUFFER: u32 = 34962;
+    const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+    const FLOAT: u32 = 5126;
+    const UNSIGNED_INT: u32 = 5125;
+
+    let mesh = ExportMesh::new(buffer, options);
+
+    let mut binary = Vec::with_capacity(mesh.positions.len() * 24 + mesh.indices.len() * 4);
+    for value in mesh.positions.iter().chain(&mesh.normals) {
+        for component in value.to_array() {
+            binary.extend_from_slice(&component.to_le_bytes());
+        }
+    }
+    for index in &mesh.indices {
+        binary.extend_from_slice(&index.to_le_bytes());
+    }
+
+    let extras = match options.lod {
+        Some(lod) => format!("{{\"lod\":{lod}}}"),
+        None => format!(
+            "{{\"buckets\":{:?},\"face_counts\":{:?}}}",
+            buffer.get_buckets(),
+            buffer.get_face_counts()
+        ),
+    };
+
+    let mut json =
+        String::from("{\"asset\":{\"version\":\"2.0\",\"generator\":\"block_mesh_pop\"},");
+    json.push_str("\"scene\":0,\"scenes\":[{\"nodes\":[0]}],");
+
+    // Accessors can't be empty, so an empty buffer has no mesh.
+    if mesh.indices.is_empty() {
+        write!(json, "\"nodes\":[{{\"extras\":{extras}}}]}}").unwrap();
+    } else {
+        let vertices = mesh.positions.len();
+        let attribute_len = vertices * 12;
+        let (min, max) = mesh.positions.iter().fold(
+            (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+            |(min, max), position| (min.min(*position), max.max(*position)),
+        );
+
+        write!(
+            json,
+            "\"nodes\":[{{\"mesh\":0,\"extras\":{extras}}}],\
+             \"meshes\":[{{\"primitives\":[{{\"attributes\":{{\"POSITION\":0,\"NORMAL\":1}},\
+             \"indices\":2,\"mode\":4}}]}}],\
+             \"buffers\":[{{\"byteLength\":{}}}],\
+             \"bufferViews\":[\
+             {{\"buffer\":0,\"byteOffset\":0,\"byteLength\":{attribute_len},\"target\":{ARRAY_BUFFER}}},\
+             {{\"buffer\":0,\"byteOffset\":{attribute_len},\"byteLength\":{attribute_len},\"target\":{ARRAY_BUFFER}}},\
+             {{\"buffer\":0,\"byteOffset\":{},\"byteLength\":{},\"target\":{ELEMENT_ARRAY_BUFFER}}}],\
+             \"accessors\":[\
+             {{\"bufferView\":0,\"componentType\":{FLOAT},\"count\":{vertices},\"type\":\"VEC3\",\
+             \"min\":[{},{},{}],\"max\":[{},{},{}]}},\
+             {{\"bufferView\":1,\"componentType\":{FLOAT},\"count\":{vertices},\"type\":\"VEC3\"}},\
+             {{\"bufferView\":2,\"componentType\":{UNSIGNED_INT},\"count\":{},\"type\":\"SCALAR\"}}]}}",
+            binary.len(),
+            attribute_len * 2,
+            mesh.indices.len() * 4,
+            min.x,
+            min.y,
+            min.z,
+            max.x,
+            max.y,
+            max.z,
+            mesh.indices.len(),
+        )
+        .unwrap();
+    }
+
+    // Chunks are padded to four bytes, JSON with spaces and binary with zeros.
+    let mut json = json.into_bytes();
+    json.resize((json.len() + 3) & !3, b' ');
+    binary.resize((binary.len() + 3) & !3, 0);
+
+    let has_binary = !binary.is_empty();
+    let total_len = 12 + 8 + json.len() + if has_binary { 8 + binary.len() } else { 0 };
+
+    writer.write_all(b"glTF")?;
+    writer.write_all(&2u32.to_le_bytes())?;
+    writer.write_all(&(total_len as u32).to_le_bytes())?;
+
+    writer.write_all(&(json.len() as u32).to_le_bytes())?;
+    writer.write_all(b"JSON")?;
+    writer.write_all(&json)?;
+
+    if has_binary {
+        writer.write_all(&(binary.len() as u32).to_le_bytes())?;
+        writer.write_all(b"BIN\0")?;
+        writer.write_all(&binary)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use bevy_math::{IVec3, Vec3, Vec3Swizzles};
+
+use crate::{ChunkShape, Fbm, Noise, NoiseKind, TerrainVoxel};
+
+/// A band of material under the surface.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Layer {
+    /// The thickness of the layer in voxels.
+    pub depth: u32,
+    pub material: u8,
+}
+
+/// Tunnels carved where the noise is close to zero.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Caves {
+    pub noise: Fbm,
+    /// How far from zero the noise is carved, so larger values give wider tunnels.
+    pub threshold: f32,
+    /// The number of voxels under the surface that caves stay below.
+    pub min_depth: u32,
+}
+
+/// Generates infinite terrain from noise, with `y` up.
+///
+/// The surface is at `base_height + amplitude * surface(x, z)`. Under it, voxels take the
+/// material of the [`layers`](Self::layers) from the top down, the last of which extends
+/// downwards. Every voxel only depends on its position, so chunks can be generated in any
+/// order and on any thread.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorldGenerator {
+    pub surface: Fbm,
+    pub base_height: f32,
+    pub amplitude: f32,
+    pub caves: Option<Caves>,
+    pub layers: Vec<Layer>,
+}
+
+impl WorldGenerator {
+    /// Hills of grass over dirt and stone, with caves.
+    pub fn new(seed: u32) -> Self {
+        Self {
+            surface: Fbm::new(Noise::new(NoiseKind::Perlin, seed), 5, 1.0 / 128.0),
+            base_height: 0.0,
+            amplitude: 48.0,
+            caves: Some(Caves {
+                noise: Fbm::new(
+                    Noise::new(NoiseKind::Simplex, seed.wrapping_add(0x9e37_79b9)),
+                    2,
+                    1.0 / 48.0,
+                ),
+                threshold: 0.06,
+                min_depth: 4,
+            }),
+            layers: vec![
+                Layer {
+                    depth: 1,
+                    material: 1,
+                },
+                Layer {
+                    depth: 3,
+                    material: 2,
+                },
+                Layer {
+                    depth: 1,
+                    material: 3,
+                },
+            ],
+        }
+    }
+
+    /// The number of voxels under the surface at `(x, z)` above `y = 0`, which can be negative.
+    #[inline]
+    pub fn surface_height(&self, x: i32, z: i32) -> i32 {
+        let noise = self.surface.sample(Vec3::new(x as f32, 0.0, z as f32));
+        (self.base_height + self.amplitude * noise).round() as i32
+    }
+
+    #[inline]
+    pub fn get(&self, position: IVec3) -> TerrainVoxel {
+        let height = self.surface_height(position.x, position.z);
+        self.get_in_column(position, height)
+    }
+
+    #[inline]
+    fn get_in_column(&self, position: IVec3, height: i32) -> TerrainVoxel {
+        if position.y >= height {
+            return TerrainVoxel::Empty;
+        }
+
+        let depth = (height - 1 - position.y) as u32;
+
+        if let Some(caves) = &self.caves {
+            if depth >= caves.min_depth
+                && caves.noise.sample(position.as_vec3()).abs() < caves.threshold
+            {
+                return TerrainVoxel::Empty;
+            }
+        }
+
+        let mut top = 0;
+        for layer in &self.layers {
+            top += layer.depth;
+            if depth < top {
+                return TerrainVoxel::Solid(layer.material);
+            }
+        }
+
+        TerrainVoxel::Solid(self.layers.last().map_or(0, |layer| layer.material))
+    }
+
+    /// Fills the padded chunk of `X * Y * Z` voxels at `chunk`, as laid out by
+    /// [`ChunkShape::fill_padded`].
+    pub fn fill_chunk<const X: u32, const Y: u32, const Z: u32>(
+        &self,
+        chunk: IVec3,
+        voxels: &mut [TerrainVoxel],
+    ) {
+        // Positions come column by column, so the surface is only sampled once per column.
+        let mut column = None;
+        ChunkShape::<X, Y, Z>::fill_padded(chunk, voxels, |position| {
+            let height = match column {
+                Some((xz, height)) if xz == position.xz() => height,
+                _ => {
+                    let height = self.surface_height(position.x, position.z);
+                    column = Some((position.xz(), height));
+                    height
+                }
+            };
+            self.get_in_column(position, height)
+        });
+    }
+}
@@ -67,6 +67,29 @@ impl OrientedBlockFace {
         [minu_minv, maxu_minv, minu_maxv, maxu_maxv]
     }
 
+    /// Snaps `quad` to the grid of `lod`, the same way `position_into_lod` does in the shader.
+    ///
+    /// Mesh the result at `lod`, which raises front faces by the size of the LOD.
+    #[inline]
+    pub fn quad_into_lod(&self, quad: UnorientedQuad, lod: usize) -> UnorientedQuad {
+        let minimum = quad.minimum;
+        let maximum = quad.minimum + quad.width * self.u + quad.height * self.v;
+
+        let new_minimum = (((minimum - 1) >> (lod as u32)) << (lod as u32)) + 1;
+        let new_maximum = (((maximum - 1 + (1u32 << (lod as u32)).saturating_sub(1))
+            >> (lod as u32))
+            << (lod as u32))
+            + 1;
+
+        let size = new_maximum - new_minimum;
+
+        UnorientedQuad {
+            minimum: new_minimum,
+            width: size.dot(self.u),
+            height: size.dot(self.v),
+        }
+    }
+
     #[inline]
     pub fn quad_mesh_positions(
         &self,
@@ -1,4 +1,4 @@
-use bevy_math::UVec3;
+use bevy_math::{IVec3, UVec3};
 
 use super::face::{FaceStrides, OrientedBlockFace};
 
@@ -60,6 +60,39 @@ impl<const X: u32, const Y: u32, const Z: u32> ChunkShape<X, Y, Z> {
         face.n * n + face.u * u + face.v * v
     }
 
+    /// Fills the padded chunk at `chunk` with the voxel `get` returns at each position.
+    ///
+    /// Each chunk holds `(X - 2) * (Y - 2) * (Z - 2)` voxels, starting at `chunk * (shape - 2)`,
+    /// surrounded by the voxels of its neighbours so that faces on the borders of chunks are
+    /// only meshed where they are visible. Positions are visited column by column from the
+    /// bottom, so that `get` can reuse work along `y`.
+    pub fn fill_padded<V>(chunk: IVec3, voxels: &mut [V], mut get: impl FnMut(IVec3) -> V) {
+        assert_eq!(voxels.len(), (X * Y * Z) as usize);
+
+        let minimum = chunk * (Self::SHAPE - UVec3::splat(2)).as_ivec3() - IVec3::ONE;
+
+        for z in 0..Z {
+            for x in 0..X {
+                for y in 0..Y {
+                    let local = UVec3::new(x, y, z);
+                    voxels[Self::linearize(local) as usize] = get(minimum + local.as_ivec3());
+                }
+            }
+        }
+    }
+
+    /// The chunks filled by [`fill_padded`](Self::fill_padded) whose inner voxels cover the
+    /// positions from `min` to `max`, inclusive.
+    pub fn chunk_range(min: IVec3, max: IVec3) -> impl Iterator<Item = IVec3> {
+        let inner = (Self::SHAPE - UVec3::splat(2)).as_ivec3();
+        let (first, last) = (min.div_euclid(inner), max.div_euclid(inner));
+
+        (first.z..=last.z).flat_map(move |z| {
+            (first.y..=last.y)
+                .flat_map(move |y| (first.x..=last.x).map(move |x| IVec3::new(x, y, z)))
+        })
+    }
+
     #[inline]
     pub fn inner_iter<const F: usize>() -> InnerChunkShapeIterator<X, Y, Z, F> {
         InnerChunkShapeIterator::<X, Y, Z, F>::new()
@@ -1,18 +1,18 @@
 use bevy_math::UVec3;
 
 use crate::{
-    geometry::face::OrientedBlockFace, ChunkShape, MergeVoxel, MeshVoxel, PopBuffer,
+    geometry::face::OrientedBlockFace, ChunkShape, MergeVoxel, MeshVoxel, QuadMesher, QuadSink,
     UnorientedQuad, VisitedBuffer, VoxelVisibility,
 };
 
 pub fn greedy_quads<const X: u32, const Y: u32, const Z: u32, const M: usize, V: MergeVoxel>(
     voxels: &[V],
     visited: &mut VisitedBuffer,
-    pop_buffer: &mut PopBuffer<M, UnorientedQuad>,
+    output: &mut impl QuadSink<UnorientedQuad>,
 ) {
     assert_eq!(voxels.len(), (X * Y * Z) as usize);
     assert_eq!(voxels.len(), visited.visited.len());
-    assert!(M <= u8::BITS as usize);
+    assert!(M <= u16::BITS as usize);
     assert!(M <= X.ilog2() as usize);
     assert!(M <= Y.ilog2() as usize);
     assert!(M <= Z.ilog2() as usize);
@@ -83,7 +83,7 @@ pub fn greedy_quads<const X: u32, const Y: u32, const Z: u32, const M: usize, V:
 
                     mark_visited(&mut visited.visited, quad, index, u_stride, v_stride, 0);
 
-                    pop_buffer.add_quad(face_index, quad, lod)
+                    output.add_quad(face_index, quad, lod)
                 }
             }
         }
@@ -91,7 +91,7 @@ pub fn greedy_quads<const X: u32, const Y: u32, const Z: u32, const M: usize, V:
 }
 
 #[inline]
-fn face_needs_mesh<T>(visited: &[u8], index: u32, voxel: &T, neighbor: &T) -> bool
+fn face_needs_mesh<T>(visited: &[u16], index: u32, voxel: &T, neighbor: &T) -> bool
 where
     T: MeshVoxel,
 {
@@ -107,7 +107,7 @@ where
 #[inline]
 fn get_max_width<T>(
     voxels: &[T],
-    visited: &[u8],
+    visited: &[u16],
     merge_value: &T::MergeValue,
     merge_neighbor_value: &T::MergeValueFacingNeighbour,
     mut index: u32,
@@ -141,7 +141,7 @@ where
 #[inline]
 fn get_max_height<T>(
     voxels: &[T],
-    visited: &[u8],
+    visited: &[u16],
     merge_value: &T::MergeValue,
     merge_neighbor_value: &T::MergeValueFacingNeighbour,
     mut index: u32,
@@ -178,7 +178,7 @@ where
 
 #[inline]
 fn mark_visited(
-    visited: &mut [u8],
+    visited: &mut [u16],
     quad: UnorientedQuad,
     minimum_index: u32,
     u_stride: u32,
@@ -196,7 +196,7 @@ fn mark_visited(
 
 #[inline]
 fn find_max_lod<const X: u32, const Y: u32, const Z: u32, const M: usize>(
-    visited: &mut [u8],
+    visited: &mut [u16],
     quad: UnorientedQuad,
     face: OrientedBlockFace,
     u_stride: u32,
@@ -205,7 +205,7 @@ fn find_max_lod<const X: u32, const Y: u32, const Z: u32, const M: usize>(
     let mut lod: usize = 0;
 
     for i in (1..M).rev() {
-        let quad_lod = into_lod(quad, face, i);
+        let quad_lod = face.quad_into_lod(quad, i);
         let index = ChunkShape::<X, Y, Z>::linearize(quad_lod.minimum);
 
         if !has_visited_lod(visited, quad_lod, index, u_stride, v_stride, i) {
@@ -222,7 +222,7 @@ fn find_max_lod<const X: u32, const Y: u32, const Z: u32, const M: usize>(
 
 #[inline]
 fn has_visited_lod(
-    visited: &[u8],
+    visited: &[u16],
     quad: UnorientedQuad,
     minimum_index: u32,
     u_stride: u32,
@@ -242,37 +242,19 @@ fn has_visited_lod(
     true
 }
 
-#[inline]
-fn into_lod(quad: UnorientedQuad, face: OrientedBlockFace, lod: usize) -> UnorientedQuad {
-    let minimum = quad.minimum;
-    let maximum = quad.minimum + quad.width * face.u + quad.height * face.v;
-
-    let new_minimum = (((minimum - 1) >> (lod as u32)) << (lod as u32)) + 1;
-    let new_maximum = (((maximum - 1 + (1u32 << (lod as u32)).saturating_sub(1)) >> (lod as u32))
-        << (lod as u32))
-        + 1;
+/// [`QuadMesher`] for [`greedy_quads`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GreedyQuads;
 
-    let size = new_maximum - new_minimum;
+impl<V: MergeVoxel> QuadMesher<V> for GreedyQuads {
+    type Quad = UnorientedQuad;
 
-    UnorientedQuad {
-        minimum: new_minimum,
-        width: size.dot(face.u),
-        height: size.dot(face.v),
+    #[inline]
+    fn mesh_quads<const X: u32, const Y: u32, const Z: u32, const M: usize>(
+        voxels: &[V],
+        visited: &mut VisitedBuffer,
+        output: &mut impl QuadSink<Self::Quad>,
+    ) {
+        greedy_quads::<X, Y, Z, M, V>(voxels, visited, output)
     }
 }
-
-// pub fn extract_greedy_lod_buffer<const M: usize>(
-//     greedy_buffer: &PopBuffer<M, UnorientedQuad>,
-//     output_buffer: &mut QuadBuffer<UnorientedQuad>,
-//     lod: usize,
-// ) {
-//     for (face_index, group) in greedy_buffer.groups.iter().enumerate() {
-//         let face = OrientedBlockFace::FACES[face_index];
-//         let to_len = group.buckets[lod];
-//         output_buffer.groups[face_index].extend(
-//             group.quads[0..to_len]
-//                 .iter()
-//                 .map(|&quad| into_lod(quad, face, lod)),
-//         );
-//     }
-// }
@@ -0,0 +1,96 @@
+use bevy_math::UVec3;
+
+use crate::{
+    geometry::{face::OrientedBlockFace, quad::UnorientedUnitQuad},
+    visible_faces::find_max_lod,
+    QuadSink, VisitedBuffer,
+};
+
+/// Meshes a chunk of `X * Y * Z` voxels whose columns are solid up to their height, without
+/// scanning the volume.
+///
+/// `heights` holds the number of solid voxels of each column of the padded chunk, from its
+/// bottom, indexed by `x + z * X`. The quads and their LODs are the same as those of
+/// [`visible_faces_quads`](crate::visible_faces_quads) for the equivalent voxels.
+pub fn heightfield_quads<const X: u32, const Y: u32, const Z: u32, const M: usize>(
+    heights: &[u32],
+    visited: &mut VisitedBuffer,
+    output: &mut impl QuadSink<UnorientedUnitQuad>,
+) {
+    assert_eq!(heights.len(), (X * Z) as usize);
+    assert_eq!(visited.visited.len(), (X * Y * Z) as usize);
+    assert!(M <= u16::BITS as usize);
+    assert!(M <= X.ilog2() as usize);
+    assert!(M <= Y.ilog2() as usize);
+    assert!(M <= Z.ilog2() as usize);
+
+    let shape = UVec3::new(X, Y, Z);
+    let height = |column: UVec3| heights[(column.x + column.z * X) as usize];
+
+    // Quads are sorted into the order `visible_faces_quads` finds them in, so that they are
+    // given the same LODs.
+    let mut quads = Vec::new();
+
+    for (face_index, face) in OrientedBlockFace::FACES.iter().enumerate() {
+        visited.reset();
+
+        // Columns are solid below their top, so there are no faces looking down.
+        if face.n == UVec3::Y {
+            if face.is_front {
+                for x in 1..X - 1 {
+                    for z in 1..Z - 1 {
+                        let top = height(UVec3::new(x, 0, z));
+                        if (2..Y).contains(&top) {
+                            quads.push((((Y - top) * X + x) * Z + z, UVec3::new(x, top - 1, z)));
+                        }
+                    }
+                }
+
+                emit::<X, Y, Z, M>(face_index, &mut quads, visited, output);
+            }
+            continue;
+        }
+
+        let max_n = face.n.dot(shape) - 1;
+
+        for n in 1..max_n {
+            // Front faces are found from the far side of the chunk.
+            let n = if face.is_front { max_n - n } else { n };
+            for u in 1..face.u.dot(shape) - 1 {
+                let column = face.n * n + face.u * u;
+                let neighbour = if face.is_front {
+                    column + face.n
+                } else {
+                    column - face.n
+                };
+
+                for y in height(neighbour).max(1)..height(column).min(Y - 1) {
+                    quads.push((y * shape.max_element() + u, column + UVec3::Y * y));
+                }
+            }
+
+            emit::<X, Y, Z, M>(face_index, &mut quads, visited, output);
+        }
+    }
+}
+
+/// Adds quads in the order of their keys, which follow the `n`, `v` and `u` coordinates.
+#[inline]
+fn emit<const X: u32, const Y: u32, const Z: u32, const M: usize>(
+    face_index: usize,
+    quads: &mut Vec<(u32, UVec3)>,
+    visited: &mut VisitedBuffer,
+    output: &mut impl QuadSink<UnorientedUnitQuad>,
+) {
+    quads.sort_unstable_by_key(|&(key, _)| key);
+
+    for (_, minimum) in quads.drain(..) {
+        let quad = UnorientedUnitQuad { minimum };
+
+        // SAFETY: `minimum` is within the interior of the chunk, and `visited` is checked to be
+        // the size of the chunk.
+        let lod = unsafe { find_max_lod::<X, Y, Z, M>(&mut visited.visited, quad) };
+
+        output.add_quad(face_index, quad, lod);
+    }
+}
@@ -0,0 +1,301 @@
+use std::fmt;
+
+use bevy::render::{render_resource::TextureFormat, texture::Image};
+use bevy_math::{IVec3, UVec2, UVec3};
+
+use crate::{terrain::has_faces, ChunkShape, TerrainVoxel};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HeightmapError {
+    /// The data is empty, or doesn't hold a whole number of rows of its width.
+    InvalidLength,
+    UnsupportedFormat(TextureFormat),
+    /// The material map isn't the size of the heightmap.
+    SizeMismatch {
+        heightmap: UVec2,
+        materials: UVec2,
+    },
+}
+
+impl fmt::Display for HeightmapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLength => write!(f, "data isn't a non-zero number of whole rows"),
+            Self::UnsupportedFormat(format) => write!(f, "unsupported image format {format:?}"),
+            Self::SizeMismatch {
+                heightmap,
+                materials,
+            } => write!(
+                f,
+                "material map of {materials} doesn't match heightmap of {heightmap}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for HeightmapError {}
+
+/// 16-bit heights, indexed by `x + z * size.x`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Heightmap {
+    pub size: UVec2,
+    pub heights: Vec<u16>,
+}
+
+impl Heightmap {
+    /// Reads headerless 16-bit little-endian heights, in rows of `width`.
+    pub fn from_raw_le(bytes: &[u8], width: u32) -> Result<Self, HeightmapError> {
+        Self::from_raw(bytes, width, u16::from_le_bytes)
+    }
+
+    /// Reads headerless 16-bit big-endian heights, in rows of `width`.
+    pub fn from_raw_be(bytes: &[u8], width: u32) -> Result<Self, HeightmapError> {
+        Self::from_raw(bytes, width, u16::from_be_bytes)
+    }
+
+    fn from_raw(
+        bytes: &[u8],
+        width: u32,
+        from_bytes: fn([u8; 2]) -> u16,
+    ) -> Result<Self, HeightmapError> {
+        let row_len = width as usize * 2;
+        if row_len == 0 || bytes.is_empty() || bytes.len() % row_len != 0 {
+            return Err(HeightmapError::InvalidLength);
+        }
+
+        Ok(Self {
+            size: UVec2::new(width, (bytes.len() / row_len) as u32),
+            heights: bytes
+                .chunks_exact(2)
+                .map(|height| from_bytes([height[0], height[1]]))
+                .collect(),
+        })
+    }
+
+    /// Reads the first channel of an image, such as a 16-bit grayscale PNG loaded by the
+    /// `AssetServer`. 8-bit images are scaled to the full range.
+    pub fn from_image(image: &Image) -> Result<Self, HeightmapError> {
+        let size = image_size(image);
+        let heights = match image.texture_descriptor.format {
+            TextureFormat::R16Uint | TextureFormat::R16Unorm => first_channel_u16(image, 2),
+            TextureFormat::Rg16Uint | TextureFormat::Rg16Unorm => first_channel_u16(image, 4),
+            TextureFormat::Rgba16Uint | TextureFormat::Rgba16Unorm => first_channel_u16(image, 8),
+            TextureFormat::R8Unorm => image.data.iter().map(|&x| x as u16 * 257).collect(),
+            TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => image
+                .data
+                .chunks_exact(4)
+                .map(|pixel| pixel[0] as u16 * 257)
+                .collect(),
+            format => return Err(HeightmapError::UnsupportedFormat(format)),
+        };
+        check_len(size, heights.len())?;
+
+        Ok(Self { size, heights })
+    }
+
+    /// The height at `(x, z)`, clamped to the edges of the map.
+    #[inline]
+    pub fn get(&self, x: i32, z: i32) -> u16 {
+        let x = x.clamp(0, self.size.x as i32 - 1) as u32;
+        let z = z.clamp(0, self.size.y as i32 - 1) as u32;
+        self.heights[(x + z * self.size.x) as usize]
+    }
+}
+
+/// The material of each column of a [`Heightmap`], indexed by `x + z * size.x`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MaterialMap {
+    pub size: UVec2,
+    pub materials: Vec<u8>,
+}
+
+impl MaterialMap {
+    /// Reads the first channel of an 8-bit image as material indices.
+    pub fn from_image(image: &Image) -> Result<Self, HeightmapError> {
+        Self::from_channels(image, |pixel| pixel[0])
+    }
+
+    /// Reads a splat map, taking the strongest of its four channels as the material.
+    pub fn from_splat_image(image: &Image) -> Result<Self, HeightmapError> {
+        Self::from_channels(image, |pixel| {
+            // Ties go to the first channel.
+            (0..4).rev().max_by_key(|&i| pixel[i]).unwrap() as u8
+        })
+    }
+
+    fn from_channels(image: &Image, material: fn(&[u8]) -> u8) -> Result<Self, HeightmapError> {
+        let materials: Vec<u8> = match image.texture_descriptor.format {
+            TextureFormat::R8Unorm | TextureFormat::R8Uint => image
+                .data
+                .iter()
+                .map(|&x| material(&[x, 0, 0, 0]))
+                .collect(),
+            TextureFormat::Rgba8Unorm
+            | TextureFormat::Rgba8UnormSrgb
+            | TextureFormat::Rgba8Uint => image.data.chunks_exact(4).map(material).collect(),
+            format => return Err(HeightmapError::UnsupportedFormat(format)),
+        };
+        let size = image_size(image);
+        check_len(size, materials.len())?;
+
+        Ok(Self { size, materials })
+    }
+
+    #[inline]
+    pub fn get(&self, x: i32, z: i32) -> u8 {
+        let x = x.clamp(0, self.size.x as i32 - 1) as u32;
+        let z = z.clamp(0, self.size.y as i32 - 1) as u32;
+        self.materials[(x + z * self.size.x) as usize]
+    }
+}
+
+#[inline]
+fn image_size(image: &Image) -> UVec2 {
+    let size = image.texture_descriptor.size;
+    UVec2::new(size.width, size.height)
+}
+
+/// Rejects maps without any columns, or whose data doesn't match their size.
+#[inline]
+fn check_len(size: UVec2, len: usize) -> Result<(), HeightmapError> {
+    if size.x == 0 || size.y == 0 || len != size.x as usize * size.y as usize {
+        return Err(HeightmapError::InvalidLength);
+    }
+    Ok(())
+}
+
+/// Bevy decodes 16-bit images into native-endian texels, ready to upload, so they are read
+/// back the same way.
+#[inline]
+fn first_channel_u16(image: &Image, stride: usize) -> Vec<u16> {
+    image
+        .data
+        .chunks_exact(stride)
+        .map(|pixel| u16::from_ne_bytes([pixel[0], pixel[1]]))
+        .collect()
+}
+
+/// Voxel terrain from a [`Heightmap`], with `x` and `z` along the map and `y` up.
+///
+/// Each column is filled up to its height, scaled so that `u16::MAX` is `max_height` voxels.
+/// Columns outside of the map are empty.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeightmapTerrain {
+    pub heightmap: Heightmap,
+    pub materials: Option<MaterialMap>,
+    pub max_height: u32,
+}
+
+impl HeightmapTerrain {
+    pub fn new(heightmap: Heightmap, max_height: u32) -> Self {
+        Self {
+            heightmap,
+            materials: None,
+            max_height,
+        }
+    }
+
+    pub fn with_materials(mut self, materials: MaterialMap) -> Result<Self, HeightmapError> {
+        if materials.size != self.heightmap.size {
+            return Err(HeightmapError::SizeMismatch {
+                heightmap: self.heightmap.size,
+                materials: materials.size,
+            });
+        }
+
+        self.materials = Some(materials);
+        Ok(self)
+    }
+
+    /// The size of the terrain in voxels, up to its tallest column.
+    pub fn size(&self) -> UVec3 {
+        let top = self.heightmap.heights.iter().max().copied().unwrap_or(0);
+        UVec3::new(
+            self.heightmap.size.x,
+            self.scale(top),
+            self.heightmap.size.y,
+        )
+    }
+
+    /// The number of solid voxels in the column at `(x, z)`.
+    #[inline]
+    pub fn height(&self, x: i32, z: i32) -> u32 {
+        let size = self.heightmap.size.as_ivec2();
+        if x < 0 || z < 0 || x >= size.x || z >= size.y {
+            return 0;
+        }
+
+        self.scale(self.heightmap.get(x, z))
+    }
+
+    #[inline]
+    fn scale(&self, height: u16) -> u32 {
+        let height = height as u64 * self.max_height as u64;
+        ((height + u16::MAX as u64 / 2) / u16::MAX as u64) as u32
+    }
+
+    #[inline]
+    pub fn get(&self, position: IVec3) -> TerrainVoxel {
+        if position.y < 0 || position.y as u32 >= self.height(position.x, position.z) {
+            return TerrainVoxel::Empty;
+        }
+
+        let material = self
+            .materials
+            .as_ref()
+            .map_or(0, |materials| materials.get(position.x, position.z));
+        TerrainVoxel::Solid(material)
+    }
+
+    /// Fills the padded chunk of `X * Y * Z` voxels at `chunk`, as from
+    /// [`chunks`](Self::chunks).
+    pub fn fill_chunk<const X: u32, const Y: u32, const Z: u32>(
+        &self,
+        chunk: UVec3,
+        voxels: &mut [TerrainVoxel],
+    ) {
+        ChunkShape::<X, Y, Z>::fill_padded(chunk.as_ivec3(), voxels, |position| self.get(position));
+    }
+
+    /// Fills the height of each column of the padded chunk of `X * Y * Z` voxels at `chunk`,
+    /// indexed by `x + z * X`, for [`heightfield_quads`](crate::heightfield_quads).
+    ///
+    /// Unlike [`fill_chunk`](Self::fill_chunk), the padding below the bottom of the terrain is
+    /// solid, so its underside isn't meshed.
+    pub fn fill_heights<const X: u32, const Y: u32, const Z: u32>(
+        &self,
+        chunk: UVec3,
+        heights: &mut [u32],
+    ) {
+        let inner = ChunkShape::<X, Y, Z>::SHAPE - UVec3::splat(2);
+        let minimum = (chunk * inner).as_ivec3() - IVec3::ONE;
+
+        for z in 0..Z {
+            for x in 0..X {
+                let height = self.height(minimum.x + x as i32, minimum.z + z as i32);
+                heights[(x + z * X) as usize] = if height == 0 {
+                    0
+                } else {
+                    (height as i32 - minimum.y).clamp(0, Y as i32) as u32
+                };
+            }
+        }
+    }
+
+    /// Splits the terrain into padded chunks of `X * Y * Z` voxels, as filled by
+    /// [`ChunkShape::fill_padded`], skipping those without any visible face.
+    pub fn chunks<const X: u32, const Y: u32, const Z: u32>(
+        &self,
+    ) -> impl Iterator<Item = (UVec3, Vec<TerrainVoxel>)> + '_ {
+        let max = self.size().as_ivec3() - IVec3::ONE;
+
+        ChunkShape::<X, Y, Z>::chunk_range(IVec3::ZERO, max)
+            .map(|chunk| chunk.as_uvec3())
+            .filter_map(move |chunk| {
+                let mut voxels = vec![TerrainVoxel::Empty; (X * Y * Z) as usize];
+                self.fill_chunk::<X, Y, Z>(chunk, &mut voxels);
+
+                has_faces::<X, Y, Z>(&voxels).then_some((chunk, voxels))
+            })
+    }
+}
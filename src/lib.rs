@@ -1,21 +1,53 @@
 mod buffer;
+mod compressed;
+mod encoding;
+mod export;
+mod generator;
 mod geometry;
 mod greedy;
+mod heightfield;
+mod heightmap;
+mod mesh;
+mod mesher;
+mod noise;
 mod render;
+mod sdf;
+mod stream;
+mod terrain;
 mod visible_faces;
+mod vox;
+mod voxelize;
 
 use std::fmt::Debug;
 
 pub use buffer::*;
+pub use compressed::Compression;
+pub use encoding::{DecodeError, EncodeQuad};
+pub use export::*;
+pub use generator::*;
 pub use geometry::face::*;
 pub use geometry::quad::*;
 pub use geometry::shape::*;
 pub use greedy::*;
+pub use heightfield::*;
+pub use heightmap::*;
+pub use mesh::*;
+pub use mesher::*;
+pub use noise::*;
 pub use render::{
-    easing::LodEasing, material::LodMaterial, material::LodMaterialPlugin,
-    material::WrappedMaterial, LodRenderPlugin,
+    allocator::LodMeshStorage, depth_offset::LodDepthOffset, easing::LodEasing, focus::LodFocus,
+    hysteresis::LodHysteresis, indirect::LodDrawMode, loader::build_vox_mesh, loader::LodVoxChunk,
+    loader::LodVoxLoader, loader::LodVoxMeshing, loader::LodVoxModel, loader::LodVoxPlugin,
+    loader::LodVoxSettings, material::LodMaterial, material::LodMaterialPlugin,
+    material::LodTransition, material::WrappedMaterial, stream::LodStream, stream::LodStreamPlugin,
+    LodRenderPlugin,
 };
+pub use sdf::*;
+pub use stream::*;
+pub use terrain::*;
 pub use visible_faces::*;
+pub use vox::*;
+pub use voxelize::*;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum VoxelVisibility {
@@ -35,3 +67,14 @@ pub trait MergeVoxel: MeshVoxel {
     fn merge_value(&self) -> Self::MergeValue;
     fn merge_value_facing_neighbour(&self) -> Self::MergeValueFacingNeighbour;
 }
+
+/// A meshing algorithm for voxels of type `V`, so chunk systems can be generic over it.
+pub trait QuadMesher<V> {
+    type Quad: Into<UnorientedQuad> + Clone;
+
+    fn mesh_quads<const X: u32, const Y: u32, const Z: u32, const M: usize>(
+        voxels: &[V],
+        visited: &mut VisitedBuffer,
+        output: &mut impl QuadSink<Self::Quad>,
+    );
+}
@@ -0,0 +1,68 @@
+use bevy::{
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+};
+
+use crate::{OrientedBlockFace, PopBuffer, UnorientedQuad};
+
+/// Builds an ordinary mesh of `buffer` at a fixed `lod`, which needs neither
+/// [`LodMaterial`](crate::LodMaterial) nor [`LodRenderPlugin`](crate::LodRenderPlugin).
+///
+/// Positions are in voxels of `voxel_size`, including the padding of the chunk, and UVs are in
+/// voxels across each quad.
+pub fn build_lod_mesh<const M: usize, Q: Into<UnorientedQuad> + Clone>(
+    buffer: &PopBuffer<M, Q>,
+    lod: usize,
+    voxel_size: f32,
+) -> Mesh {
+    let quads = buffer.to_lod_quads(lod);
+    build_quad_mesh(quads.num_quads(), quads.iter_quads(), lod, voxel_size)
+}
+
+/// Builds the mesh of `buffer` drawn by [`LodMaterial`](crate::LodMaterial), with the quads in
+/// the order counted by [`PopBuffer::get_face_counts`].
+///
+/// UVs are in voxels across each quad.
+pub fn build_pop_mesh<const M: usize, Q: Into<UnorientedQuad> + Clone>(
+    buffer: &PopBuffer<M, Q>,
+) -> Mesh {
+    let quads = buffer
+        .quads()
+        .map(|(face, quad)| (face, quad.clone().into()));
+    build_quad_mesh(buffer.num_quads(), quads, 0, 1.0)
+}
+
+fn build_quad_mesh(
+    num_quads: usize,
+    quads: impl Iterator<Item = (OrientedBlockFace, UnorientedQuad)>,
+    lod: usize,
+    voxel_size: f32,
+) -> Mesh {
+    let mut indices = Vec::with_capacity(num_quads * 6);
+    let mut positions = Vec::with_capacity(num_quads * 4);
+    let mut normals = Vec::with_capacity(num_quads * 4);
+    let mut uvs = Vec::with_capacity(num_quads * 4);
+
+    for (face, quad) in quads {
+        let (width, height) = (quad.width as f32, quad.height as f32);
+
+        indices.extend_from_slice(&face.quad_mesh_indices(positions.len() as u32));
+        positions.extend_from_slice(&face.quad_mesh_positions(quad, lod, voxel_size));
+        normals.extend_from_slice(&face.quad_mesh_normals());
+        uvs.extend_from_slice(&[
+            Vec2::new(0.0, 0.0),
+            Vec2::new(width, 0.0),
+            Vec2::new(0.0, height),
+            Vec2::new(width, height),
+        ]);
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U32(indices)));
+
+    mesh
+}
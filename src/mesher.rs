@@ -0,0 +1,65 @@
+use crate::{
+    greedy_quads, heightfield_quads, visible_faces_quads, MergeVoxel, MeshVoxel, PopBuffer,
+    QuadMesher, UnorientedQuad, UnorientedUnitQuad, VisitedBuffer,
+};
+
+/// Meshes chunks of `X * Y * Z` voxels, reusing its buffers between calls.
+///
+/// Each call clears the buffers before meshing, so a mesher can be kept per worker thread and
+/// used for any number of chunks. The returned buffers borrow the mesher until the next call.
+pub struct Mesher<const X: u32, const Y: u32, const Z: u32, const M: usize> {
+    visited: VisitedBuffer,
+    unit_quads: PopBuffer<M, UnorientedUnitQuad>,
+    quads: PopBuffer<M, UnorientedQuad>,
+}
+
+impl<const X: u32, const Y: u32, const Z: u32, const M: usize> Default for Mesher<X, Y, Z, M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const X: u32, const Y: u32, const Z: u32, const M: usize> Mesher<X, Y, Z, M> {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            visited: VisitedBuffer::new((X * Y * Z) as usize),
+            unit_quads: PopBuffer::new(),
+            quads: PopBuffer::new(),
+        }
+    }
+
+    /// Meshes `voxels` with [`visible_faces_quads`].
+    pub fn mesh_visible_faces<V: MeshVoxel>(
+        &mut self,
+        voxels: &[V],
+    ) -> &PopBuffer<M, UnorientedUnitQuad> {
+        self.unit_quads.reset();
+        visible_faces_quads::<X, Y, Z, M, V>(voxels, &mut self.visited, &mut self.unit_quads);
+        &self.unit_quads
+    }
+
+    /// Meshes `voxels` with [`greedy_quads`].
+    pub fn mesh_greedy<V: MergeVoxel>(&mut self, voxels: &[V]) -> &PopBuffer<M, UnorientedQuad> {
+        self.quads.reset();
+        greedy_quads::<X, Y, Z, M, V>(voxels, &mut self.visited, &mut self.quads);
+        &self.quads
+    }
+
+    /// Meshes the columns of `heights` with [`heightfield_quads`].
+    pub fn mesh_heightfield(&mut self, heights: &[u32]) -> &PopBuffer<M, UnorientedUnitQuad> {
+        self.unit_quads.reset();
+        heightfield_quads::<X, Y, Z, M>(heights, &mut self.visited, &mut self.unit_quads);
+        &self.unit_quads
+    }
+
+    /// Meshes `voxels` with any [`QuadMesher`] into `output`, clearing it first.
+    pub fn mesh_into<A: QuadMesher<V>, V>(
+        &mut self,
+        voxels: &[V],
+        output: &mut PopBuffer<M, A::Quad>,
+    ) {
+        output.reset();
+        A::mesh_quads::<X, Y, Z, M>(voxels, &mut self.visited, output);
+    }
+}
@@ -0,0 +1,211 @@
+use bevy_math::{IVec3, Vec3};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NoiseKind {
+    Value,
+    #[default]
+    Perlin,
+    Simplex,
+}
+
+/// Seeded gradient or value noise in three dimensions, in `-1.0..=1.0`.
+///
+/// Lattice values are hashed from their coordinates and the seed rather than looked up in a
+/// table, so noise is deterministic everywhere and can be sampled from any thread.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Noise {
+    pub kind: NoiseKind,
+    pub seed: u32,
+}
+
+impl Noise {
+    #[inline]
+    pub fn new(kind: NoiseKind, seed: u32) -> Self {
+        Self { kind, seed }
+    }
+
+    #[inline]
+    pub fn sample(&self, position: Vec3) -> f32 {
+        let value = match self.kind {
+            NoiseKind::Value => self.value(position),
+            NoiseKind::Perlin => self.perlin(position),
+            NoiseKind::Simplex => self.simplex(position),
+        };
+        value.clamp(-1.0, 1.0)
+    }
+
+    #[inline]
+    fn hash(&self, cell: IVec3) -> u32 {
+        // The seed and each coordinate are mixed in turn, so that nearby seeds aren't offsets
+        // of each other.
+        let mut hash = mix(self.seed);
+        for coordinate in cell.to_array() {
+            hash = mix(hash ^ coordinate as u32);
+        }
+        hash
+    }
+
+    #[inline]
+    fn gradient(&self, cell: IVec3, offset: Vec3) -> f32 {
+        // The twelve edges of a cube, with four repeated to take sixteen values.
+        let (x, y, z) = (offset.x, offset.y, offset.z);
+        match self.hash(cell) >> 28 {
+            0 | 12 => x + y,
+            1 | 13 => -x + y,
+            2 => x - y,
+            3 => -x - y,
+            4 => x + z,
+            5 => -x + z,
+            6 => x - z,
+            7 => -x - z,
+            8 => y + z,
+            9 | 14 => -y + z,
+            10 => y - z,
+            _ => -y - z,
+        }
+    }
+
+    fn value(&self, position: Vec3) -> f32 {
+        let cell = position.floor();
+        let t = fade(position - cell);
+        let cell = cell.as_ivec3();
+
+        let corner = |x, y, z| {
+            let hash = self.hash(cell + IVec3::new(x, y, z));
+            hash as f32 / u32::MAX as f32 * 2.0 - 1.0
+        };
+
+        trilinear(t, corner)
+    }
+
+    fn perlin(&self, position: Vec3) -> f32 {
+        let cell = position.floor();
+        let offset = position - cell;
+        let t = fade(offset);
+        let cell = cell.as_ivec3();
+
+        let corner = |x, y, z| {
+            let corner = IVec3::new(x, y, z);
+            self.gradient(cell + corner, offset - corner.as_vec3())
+        };
+
+        trilinear(t, corner)
+    }
+
+    fn simplex(&self, position: Vec3) -> f32 {
+        const SKEW: f32 = 1.0 / 3.0;
+        const UNSKEW: f32 = 1.0 / 6.0;
+
+        let cell = (position + Vec3::splat(position.dot(Vec3::ONE) * SKEW)).floor();
+        let origin = cell - Vec3::splat(cell.dot(Vec3::ONE) * UNSKEW);
+        let offset = position - origin;
+        let cell = cell.as_ivec3();
+
+        // The simplex is walked from the origin of the cell along its largest offsets first.
+        let (first, second) = if offset.x >= offset.y {
+            if offset.y >= offset.z {
+                (IVec3::X, IVec3::new(1, 1, 0))
+            } else if offset.x >= offset.z {
+                (IVec3::X, IVec3::new(1, 0, 1))
+            } else {
+                (IVec3::Z, IVec3::new(1, 0, 1))
+            }
+        } else if offset.y < offset.z {
+            (IVec3::Z, IVec3::new(0, 1, 1))
+        } else if offset.x < offset.z {
+            (IVec3::Y, IVec3::new(0, 1, 1))
+        } else {
+            (IVec3::Y, IVec3::new(1, 1, 0))
+        };
+
+        let mut value = 0.0;
+        for (i, corner) in [IVec3::ZERO, first, second, IVec3::ONE]
+            .into_iter()
+            .enumerate()
+        {
+            let offset = offset - corner.as_vec3() + Vec3::splat(i as f32 * UNSKEW);
+            let falloff = 0.6 - offset.length_squared();
+            if falloff > 0.0 {
+                value += falloff.powi(4) * self.gradient(cell + corner, offset);
+            }
+        }
+
+        value * 32.0
+    }
+}
+
+/// The finalizer of MurmurHash3.
+#[inline]
+fn mix(mut hash: u32) -> u32 {
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x85eb_ca6b);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0xc2b2_ae35);
+    hash ^ (hash >> 16)
+}
+
+#[inline]
+fn fade(t: Vec3) -> Vec3 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+#[inline]
+fn trilinear(t: Vec3, corner: impl Fn(i32, i32, i32) -> f32) -> f32 {
+    let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+
+    let x00 = lerp(corner(0, 0, 0), corner(1, 0, 0), t.x);
+    let x10 = lerp(corner(0, 1, 0), corner(1, 1, 0), t.x);
+    let x01 = lerp(corner(0, 0, 1), corner(1, 0, 1), t.x);
+    let x11 = lerp(corner(0, 1, 1), corner(1, 1, 1), t.x);
+
+    lerp(lerp(x00, x10, t.y), lerp(x01, x11, t.y), t.z)
+}
+
+/// Fractal Brownian motion, summing octaves of [`Noise`] of rising frequency and falling
+/// amplitude, normalized to `-1.0..=1.0`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fbm {
+    pub noise: Noise,
+    pub octaves: u32,
+    /// The frequency of the first octave, in cycles per voxel.
+    pub frequency: f32,
+    /// How much the frequency is multiplied by for each octave.
+    pub lacunarity: f32,
+    /// How much the amplitude is multiplied by for each octave.
+    pub gain: f32,
+}
+
+impl Fbm {
+    #[inline]
+    pub fn new(noise: Noise, octaves: u32, frequency: f32) -> Self {
+        Self {
+            noise,
+            octaves,
+            frequency,
+            lacunarity: 2.0,
+            gain: 0.5,
+        }
+    }
+
+    pub fn sample(&self, position: Vec3) -> f32 {
+        let mut value = 0.0;
+        let mut total = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = self.frequency;
+
+        for octave in 0..self.octaves {
+            // Octaves are decorrelated by their seeds.
+            let noise = Noise::new(self.noise.kind, self.noise.seed.wrapping_add(octave));
+            value += noise.sample(position * frequency) * amplitude;
+            total += amplitude;
+            amplitude *= self.gain;
+            frequency *= self.lacunarity;
+        }
+
+        if total > 0.0 {
+            value / total
+        } else {
+            0.0
+        }
+    }
+}
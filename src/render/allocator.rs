@@ -0,0 +1,406 @@
+use std::{marker::PhantomData, mem, ops::Range};
+
+use bevy::{
+    prelude::*,
+    render::{
+        extract_resource::ExtractResource,
+        mesh::{Indices, MeshVertexBufferLayout, PrimitiveTopology},
+        render_resource::{
+            Buffer, BufferDescriptor, BufferSlice, BufferUsages, CommandEncoderDescriptor,
+        },
+        renderer::{RenderDevice, RenderQueue},
+        Extract,
+    },
+    utils::{HashMap, HashSet},
+};
+
+use super::material::{LodMaterial, WrappedMaterial};
+
+/// Selects where the vertices and indices of meshes drawn with a [`LodMaterial`] are stored.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Resource, ExtractResource)]
+pub enum LodMeshStorage {
+    /// Each mesh is drawn from the buffers bevy creates for it.
+    #[default]
+    Separate,
+    /// Meshes are copied into ranges of buffers shared by every mesh with the same material type,
+    /// so that remeshing reuses their space and consecutive draws do not rebind buffers.
+    ///
+    /// A range is only freed when its `Mesh` asset is removed or modified, so meshes that are no
+    /// longer drawn keep their space. Free ranges are only defragmented when an allocation does
+    /// not fit, by copying every mesh into new buffers.
+    ///
+    /// Meshes that are not indexed triangle lists of quads, or whose vertex layout differs from
+    /// the first shared mesh, stay in their own buffers.
+    Shared,
+}
+
+/// Hands out ranges of quads from a buffer holding `capacity` quads.
+#[derive(Debug, Default)]
+pub(crate) struct QuadAllocator {
+    capacity: u32,
+    /// The unallocated ranges, sorted and never adjacent.
+    free: Vec<Range<u32>>,
+}
+
+impl QuadAllocator {
+    /// Allocates `quads` quads from the first free range large enough to hold them.
+    fn allocate(&mut self, quads: u32) -> Option<Range<u32>> {
+        if quads == 0 {
+            return Some(0..0);
+        }
+
+        let index = self
+            .free
+            .iter()
+            .position(|range| range.end - range.start >= quads)?;
+
+        let range = &mut self.free[index];
+        let allocation = range.start..(range.start + quads);
+
+        range.start += quads;
+        if range.start == range.end {
+            self.free.remove(index);
+        }
+
+        Some(allocation)
+    }
+
+    fn free(&mut self, range: Range<u32>) {
+        if range.is_empty() {
+            return;
+        }
+
+        let mut index = self.free.partition_point(|free| free.start < range.start);
+        self.free.insert(index, range);
+
+        if index + 1 < self.free.len() && self.free[index].end == self.free[index + 1].start {
+            self.free[index].end = self.free.remove(index + 1).end;
+        }
+
+        if index > 0 && self.free[index - 1].end == self.free[index].start {
+            self.free[index - 1].end = self.free.remove(index).end;
+            index -= 1;
+        }
+
+        debug_assert!(self.free[index].end <= self.capacity);
+    }
+
+    fn free_quads(&self) -> u32 {
+        self.free.iter().map(|range| range.end - range.start).sum()
+    }
+}
+
+/// The vertices and indices of a mesh to copy into the shared buffers.
+pub struct ExtractedLodMesh {
+    layout: MeshVertexBufferLayout,
+    vertices: Vec<u8>,
+    indices: Vec<u32>,
+}
+
+impl ExtractedLodMesh {
+    /// Returns `None` for meshes that are not made of quads of four vertices and six indices.
+    fn new(mesh: &Mesh) -> Option<Self> {
+        if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+            return None;
+        }
+
+        let indices: Vec<u32> = match mesh.indices()? {
+            Indices::U16(indices) => indices.iter().map(|&index| index as u32).collect(),
+            Indices::U32(indices) => indices.clone(),
+        };
+
+        if indices.len() % 6 != 0 || mesh.count_vertices() != indices.len() / 6 * 4 {
+            return None;
+        }
+
+        Some(Self {
+            layout: mesh.get_mesh_vertex_buffer_layout(),
+            vertices: mesh.get_vertex_buffer_data(),
+            indices,
+        })
+    }
+
+    fn quads(&self) -> u32 {
+        (self.indices.len() / 6) as u32
+    }
+}
+
+#[derive(Resource)]
+pub struct ExtractedLodMeshes<const U: usize, M: Material> {
+    extracted: Vec<(Handle<Mesh>, ExtractedLodMesh)>,
+    removed: Vec<Handle<Mesh>>,
+    marker: PhantomData<M>,
+}
+
+impl<const U: usize, M: Material> Default for ExtractedLodMeshes<U, M> {
+    fn default() -> Self {
+        Self {
+            extracted: default(),
+            removed: default(),
+            marker: PhantomData,
+        }
+    }
+}
+
+/// A mesh within the shared buffers.
+pub(crate) struct SharedLodMesh<'a> {
+    pub vertex_buffer: BufferSlice<'a>,
+    pub index_buffer: BufferSlice<'a>,
+    /// The offset of the mesh within both buffers, in quads. Its indices start from zero, so
+    /// draws pass `first_quad * 4` as the base vertex.
+    pub first_quad: u32,
+}
+
+/// The shared buffers holding meshes drawn with a [`LodMaterial`] with `U` LODs, and the range
+/// of quads allocated to each mesh.
+#[derive(Resource)]
+pub struct RenderLodMeshes<const U: usize, M: Material> {
+    layout: Option<MeshVertexBufferLayout>,
+    vertex_buffer: Option<Buffer>,
+    index_buffer: Option<Buffer>,
+    allocator: QuadAllocator,
+    allocations: HashMap<Handle<Mesh>, Range<u32>>,
+    /// Meshes that cannot be shared, so that they are not extracted again until modified.
+    separate: HashSet<Handle<Mesh>>,
+    marker: PhantomData<M>,
+}
+
+impl<const U: usize, M: Material> Default for RenderLodMeshes<U, M> {
+    fn default() -> Self {
+        Self {
+            layout: None,
+            vertex_buffer: None,
+            index_buffer: None,
+            allocator: default(),
+            allocations: default(),
+            separate: default(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<const U: usize, M: Material> RenderLodMeshes<U, M> {
+    const INITIAL_CAPACITY: u32 = 1 << 16;
+
+    pub(crate) fn get(&self, handle: &Handle<Mesh>) -> Option<SharedLodMesh<'_>> {
+        let allocation = self.allocations.get(handle)?;
+
+        Some(SharedLodMesh {
+            vertex_buffer: self.vertex_buffer.as_ref()?.slice(..),
+            index_buffer: self.index_buffer.as_ref()?.slice(..),
+            first_quad: allocation.start,
+        })
+    }
+
+    fn vertex_stride(&self) -> u64 {
+        self.layout
+            .as_ref()
+            .map_or(0, |layout| layout.layout().array_stride)
+    }
+
+    fn insert(
+        &mut self,
+        handle: Handle<Mesh>,
+        mesh: ExtractedLodMesh,
+        render_device: &RenderDevice,
+        render_queue: &RenderQueue,
+    ) {
+        match &self.layout {
+            Some(layout) if *layout != mesh.layout => {
+                self.separate.insert(handle);
+                return;
+            }
+            Some(_) => {}
+            None => self.layout = Some(mesh.layout.clone()),
+        }
+
+        let quads = mesh.quads();
+        let allocation = match self.allocator.allocate(quads) {
+            Some(allocation) => allocation,
+            None => {
+                // Compact the buffers if that frees enough space, and grow them otherwise.
+                let used = self.allocator.capacity - self.allocator.free_quads();
+                let capacity = if self.allocator.free_quads() >= quads {
+                    self.allocator.capacity
+                } else {
+                    (used + quads)
+                        .max(self.allocator.capacity * 2)
+                        .max(Self::INITIAL_CAPACITY)
+                        .next_power_of_two()
+                };
+
+                self.repack(capacity, render_device, render_queue);
+                self.allocator.allocate(quads).unwrap()
+            }
+        };
+
+        let vertex_offset = allocation.start as u64 * 4 * self.vertex_stride();
+        let index_offset = allocation.start as u64 * 6 * mem::size_of::<u32>() as u64;
+
+        if let (Some(vertex_buffer), Some(index_buffer)) = (&self.vertex_buffer, &self.index_buffer)
+        {
+            render_queue.write_buffer(vertex_buffer, vertex_offset, &mesh.vertices);
+            render_queue.write_buffer(
+                index_buffer,
+                index_offset,
+                bytemuck::cast_slice(&mesh.indices),
+            );
+        }
+
+        self.allocations.insert(handle, allocation);
+    }
+
+    fn remove(&mut self, handle: &Handle<Mesh>) {
+        if let Some(allocation) = self.allocations.remove(handle) {
+            self.allocator.free(allocation);
+        }
+    }
+
+    /// Moves every allocation to the start of new buffers holding `capacity` quads.
+    ///
+    /// Writes queued for the old buffers are applied before the copies, since they are submitted
+    /// with them.
+    fn repack(&mut self, capacity: u32, render_device: &RenderDevice, render_queue: &RenderQueue) {
+        let vertex_size = 4 * self.vertex_stride();
+        let index_size = 6 * mem::size_of::<u32>() as u64;
+
+        let vertex_buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("lod_mesh_vertex_buffer"),
+            size: capacity as u64 * vertex_size,
+            usage: BufferUsages::VERTEX | BufferUsages::COPY_DST | BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let index_buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("lod_mesh_index_buffer"),
+            size: capacity as u64 * index_size,
+            usage: BufferUsages::INDEX | BufferUsages::COPY_DST | BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = render_device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("lod_mesh_repack"),
+        });
+
+        let mut start = 0;
+        if let (Some(old_vertex_buffer), Some(old_index_buffer)) =
+            (&self.vertex_buffer, &self.index_buffer)
+        {
+            for allocation in self.allocations.values_mut() {
+                let quads = allocation.end - allocation.start;
+
+                encoder.copy_buffer_to_buffer(
+                    old_vertex_buffer,
+                    allocation.start as u64 * vertex_size,
+                    &vertex_buffer,
+                    start as u64 * vertex_size,
+                    quads as u64 * vertex_size,
+                );
+                encoder.copy_buffer_to_buffer(
+                    old_index_buffer,
+                    allocation.start as u64 * index_size,
+                    &index_buffer,
+                    start as u64 * index_size,
+                    quads as u64 * index_size,
+                );
+
+                *allocation = start..(start + quads);
+                start += quads;
+            }
+        }
+
+        render_queue.submit([encoder.finish()]);
+
+        self.vertex_buffer = Some(vertex_buffer);
+        self.index_buffer = Some(index_buffer);
+        self.allocator = QuadAllocator {
+            capacity,
+            free: Vec::new(),
+        };
+        self.allocator.free(start..capacity);
+    }
+}
+
+type LodMeshFilter<const U: usize, M> = (With<Handle<LodMaterial<U>>>, With<WrappedMaterial<M>>);
+
+pub(crate) fn extract_lod_meshes<const U: usize, M: Material>(
+    mut commands: Commands,
+    storage: Extract<Option<Res<LodMeshStorage>>>,
+    mut events: Extract<EventReader<AssetEvent<Mesh>>>,
+    meshes: Extract<Res<Assets<Mesh>>>,
+    lod_meshes: Extract<Query<&Handle<Mesh>, LodMeshFilter<U, M>>>,
+    mut render_meshes: ResMut<RenderLodMeshes<U, M>>,
+) {
+    let mut removed = Vec::new();
+
+    if storage.as_deref() != Some(&LodMeshStorage::Shared) {
+        removed.extend(render_meshes.allocations.keys().cloned());
+        commands.insert_resource(ExtractedLodMeshes::<U, M> {
+            extracted: Vec::new(),
+            removed,
+            marker: PhantomData,
+        });
+        return;
+    }
+
+    let mut changed = HashSet::new();
+    for event in events.iter() {
+        match event {
+            AssetEvent::Created { handle } => {
+                changed.insert(handle.clone_weak());
+            }
+            AssetEvent::Modified { handle } => {
+                changed.insert(handle.clone_weak());
+                render_meshes.separate.remove(handle);
+                removed.push(handle.clone_weak());
+            }
+            AssetEvent::Removed { handle } => {
+                changed.remove(handle);
+                render_meshes.separate.remove(handle);
+                removed.push(handle.clone_weak());
+            }
+        }
+    }
+
+    let mut extracted = Vec::new();
+    let mut seen = HashSet::new();
+    for handle in &lod_meshes {
+        let is_new = !render_meshes.allocations.contains_key(handle)
+            && !render_meshes.separate.contains(handle);
+
+        if !(is_new || changed.contains(handle)) || !seen.insert(handle.clone_weak()) {
+            continue;
+        }
+
+        let Some(mesh) = meshes.get(handle) else {
+            continue;
+        };
+
+        match ExtractedLodMesh::new(mesh) {
+            Some(mesh) => extracted.push((handle.clone_weak(), mesh)),
+            None => {
+                render_meshes.separate.insert(handle.clone_weak());
+            }
+        }
+    }
+
+    commands.insert_resource(ExtractedLodMeshes::<U, M> {
+        extracted,
+        removed,
+        marker: PhantomData,
+    });
+}
+
+pub(crate) fn prepare_lod_meshes<const U: usize, M: Material>(
+    mut extracted: ResMut<ExtractedLodMeshes<U, M>>,
+    mut render_meshes: ResMut<RenderLodMeshes<U, M>>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    for handle in mem::take(&mut extracted.removed) {
+        render_meshes.remove(&handle);
+    }
+
+    for (handle, mesh) in mem::take(&mut extracted.extracted) {
+        render_meshes.insert(handle, mesh, &render_device, &render_queue);
+    }
+}
@@ -0,0 +1,47 @@
+use bevy::{
+    prelude::*,
+    render::{primitives::Aabb, view::NoFrustumCulling},
+    utils::HashSet,
+};
+
+use super::material::LodMaterial;
+
+type LodBoundsQuery<'a, const U: usize> = (
+    Entity,
+    Ref<'a, Handle<LodMaterial<U>>>,
+    Option<&'a mut Aabb>,
+);
+
+/// Replaces the [`Aabb`] bevy computes from the LOD-0 positions of a mesh with one covering the
+/// mesh at every LOD of its [`LodMaterial`].
+///
+/// This runs after `calculate_bounds`, so that its insertions are applied after bevy's.
+pub(crate) fn update_lod_aabbs<const U: usize>(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<LodMaterial<U>>>,
+    materials: Res<Assets<LodMaterial<U>>>,
+    mut meshes: Query<LodBoundsQuery<U>, Without<NoFrustumCulling>>,
+) {
+    let mut changed_materials = HashSet::new();
+    for event in events.iter() {
+        if let AssetEvent::Created { handle } | AssetEvent::Modified { handle } = event {
+            changed_materials.insert(handle.clone_weak());
+        }
+    }
+
+    for (entity, material_handle, aabb) in &mut meshes {
+        let changed = material_handle.is_changed() || changed_materials.contains(&*material_handle);
+
+        let Some(material) = materials.get(&material_handle) else {
+            continue;
+        };
+
+        match aabb {
+            Some(mut aabb) if changed => *aabb = material.aabb(),
+            Some(_) => {}
+            None => {
+                commands.entity(entity).insert(material.aabb());
+            }
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use bevy::render::render_resource::{DepthBiasState, ShaderDefVal};
+
+/// How the faces of the outgoing LOD are kept from z-fighting with the faces of the incoming LOD
+/// while geomorphing between them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LodDepthOffset {
+    None,
+    /// Moves outgoing faces inwards along their normal by up to `depth_offset_scale` voxels,
+    /// staggered between neighbouring quads.
+    Nudge,
+    /// Pushes the mesh away from the view with a pipeline [`DepthBiasState`], whose slope scale
+    /// is `depth_offset_scale`. The bias applies to every face of the mesh, so it keeps the mesh
+    /// behind coplanar faces of its neighbours rather than separating its own LODs.
+    DepthBias,
+}
+
+impl LodDepthOffset {
+    /// The depth bias of the pipeline drawing a material with this offset and
+    /// `depth_offset_scale`.
+    pub(crate) fn depth_bias(self, depth_offset_scale: f32) -> Option<DepthBiasState> {
+        // With reverse-z, a negative bias pushes faces away from the view.
+        (self == Self::DepthBias).then_some(DepthBiasState {
+            constant: -1,
+            slope_scale: -depth_offset_scale,
+            clamp: 0.0,
+        })
+    }
+}
+
+impl From<LodDepthOffset> for ShaderDefVal {
+    fn from(value: LodDepthOffset) -> Self {
+        let name = match value {
+            LodDepthOffset::None => "DEPTH_OFFSET_NONE",
+            LodDepthOffset::Nudge => "DEPTH_OFFSET_NUDGE",
+            LodDepthOffset::DepthBias => "DEPTH_OFFSET_BIAS",
+        };
+
+        Self::Bool(name.into(), true)
+    }
+}
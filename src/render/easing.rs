@@ -1,38 +1,87 @@
 use bevy::render::render_resource::ShaderDefVal;
 
+/// The discriminants match the `EASING_*` constants of `lod_functions.wgsl`.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(u32)]
 pub enum LodEasing {
-    Linear,
-    Quadratic,
-    Cubic,
-    Sine,
+    Linear = 0,
+    Quadratic = 1,
+    Cubic = 2,
+    Sine = 3,
+    /// Selects the coarsest LOD whose quantisation step projects to at most
+    /// `pixel_tolerance` pixels on screen. `period` is ignored, and distances are always measured
+    /// from the view since the projection depends on it.
+    ScreenSpace = 4,
+    /// Interpolates linearly between the distances in `lod_distances`, where the `n`th distance
+    /// is the one at which LOD `n` starts. `period` is ignored.
+    ///
+    /// There is no distance for LOD `U`, so this tops out at `max_lod.min(U - 1)`, one short of
+    /// the coarsest LOD the other easings reach when `max_lod` is `U`.
+    Piecewise = 5,
 }
 
 impl LodEasing {
-    pub(crate) fn calculate(&self, distance: f32, period: u32, max_lod: u32) -> f32 {
+    /// The index the shaders select this easing by.
+    #[inline]
+    pub(crate) fn id(self) -> u32 {
+        self as u32
+    }
+
+    /// The LOD selected at `distance` from the focus, for a mesh whose voxels are
+    /// `voxel_pixels` pixels high on screen, with `lod_distances` holding one distance per LOD.
+    ///
+    /// This mirrors `ease_lod` in `lod_functions.wgsl`.
+    pub fn calculate(
+        &self,
+        distance: f32,
+        voxel_pixels: f32,
+        period: u32,
+        max_lod: u32,
+        pixel_tolerance: f32,
+        lod_distances: &[f32],
+    ) -> f32 {
         let period = period as f32;
-        let max_lod = max_lod as f32;
-        let distance = distance.clamp(0.0, period);
+        let clamped_distance = distance.clamp(0.0, period);
         match *self {
-            Self::Linear => max_lod / period * distance,
-            Self::Quadratic => max_lod * (distance / period).powi(2),
-            Self::Cubic => max_lod * (distance / period).powi(3),
+            Self::Linear => max_lod as f32 / period * clamped_distance,
+            Self::Quadratic => max_lod as f32 * (clamped_distance / period).powi(2),
+            Self::Cubic => max_lod as f32 * (clamped_distance / period).powi(3),
             Self::Sine => {
-                max_lod - max_lod * (std::f32::consts::PI * distance / (2.0 * period)).cos()
+                let max_lod = max_lod as f32;
+                max_lod - max_lod * (std::f32::consts::PI * clamped_distance / (2.0 * period)).cos()
             }
+            // A quantisation step of 2^lod voxels covers 2^lod * voxel_pixels pixels, so the
+            // coarsest LOD within tolerance is the floor of log2(tolerance / voxel_pixels).
+            Self::ScreenSpace => (pixel_tolerance / voxel_pixels)
+                .log2()
+                .clamp(0.0, max_lod as f32),
+            Self::Piecewise => piecewise_lod(distance, max_lod, lod_distances),
         }
     }
 }
 
+/// This mirrors `piecewise_lod` in `lod_functions.wgsl`.
+fn piecewise_lod(distance: f32, max_lod: u32, lod_distances: &[f32]) -> f32 {
+    let lod_distance = |lod: u32| lod_distances[lod as usize];
+    let last_lod = max_lod.min(lod_distances.len() as u32 - 1);
+
+    let mut lod = 0;
+    while lod < last_lod && distance >= lod_distance(lod + 1) {
+        lod += 1;
+    }
+
+    if lod == last_lod {
+        return last_lod as f32;
+    }
+
+    let start = lod_distance(lod);
+    let end = lod_distance(lod + 1);
+
+    (lod as f32 + (distance - start) / (end - start)).max(0.0)
+}
+
 impl From<LodEasing> for ShaderDefVal {
     fn from(value: LodEasing) -> Self {
-        let name = match value {
-            LodEasing::Linear => "EASING_LINEAR",
-            LodEasing::Quadratic => "EASING_QUADRATIC",
-            LodEasing::Cubic => "EASING_CUBIC",
-            LodEasing::Sine => "EASING_SINE",
-        };
-
-        Self::Bool(name.into(), true)
+        Self::UInt("EASING".into(), value.id())
     }
 }
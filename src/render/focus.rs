@@ -0,0 +1,69 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_resource::{ShaderType, UniformBuffer},
+        renderer::{RenderDevice, RenderQueue},
+        Extract,
+    },
+};
+
+/// Marks the entity from which LOD distances are measured.
+///
+/// Without a focus, distances are measured from the camera rendering the mesh. Only a single
+/// focus is supported; if several entities are marked, an arbitrary one is used.
+#[derive(Clone, Copy, Debug, Default, Component)]
+pub struct LodFocus;
+
+#[derive(Clone, Copy, Debug, Default, Resource)]
+pub struct ExtractedLodFocus(pub Option<Vec3>);
+
+#[derive(Clone, Copy, Default, ShaderType)]
+pub struct GpuLodFocus {
+    pub position: Vec3,
+    pub enabled: u32,
+}
+
+#[derive(Resource)]
+pub struct LodFocusUniform {
+    pub buffer: UniformBuffer<GpuLodFocus>,
+}
+
+impl FromWorld for LodFocusUniform {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let render_queue = world.resource::<RenderQueue>();
+
+        // The buffer is created up front so that material bind groups can reference it before the
+        // first frame is prepared; afterwards it is only ever written in place.
+        let mut buffer = UniformBuffer::from(GpuLodFocus::default());
+        buffer.set_label(Some("lod_focus_uniform_buffer"));
+        buffer.write_buffer(render_device, render_queue);
+
+        Self { buffer }
+    }
+}
+
+pub(crate) fn extract_lod_focus(
+    mut commands: Commands,
+    focuses: Extract<Query<&GlobalTransform, With<LodFocus>>>,
+) {
+    let position = focuses
+        .iter()
+        .next()
+        .map(|transform| transform.translation());
+
+    commands.insert_resource(ExtractedLodFocus(position));
+}
+
+pub(crate) fn prepare_lod_focus(
+    focus: Res<ExtractedLodFocus>,
+    mut uniform: ResMut<LodFocusUniform>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    uniform.buffer.set(GpuLodFocus {
+        position: focus.0.unwrap_or_default(),
+        enabled: focus.0.is_some() as u32,
+    });
+    uniform.buffer.write_buffer(&render_device, &render_queue);
+}
@@ -0,0 +1,58 @@
+use std::marker::PhantomData;
+
+use bevy::{prelude::*, render::extract_component::ExtractComponent, utils::HashMap};
+
+/// Delays LOD changes of a mesh until its LOD has moved a margin past the boundary between two
+/// LODs, which stops the mesh from flickering while the view hovers near the boundary.
+///
+/// The margin is measured in LOD levels so that it applies the same way to every [`LodEasing`].
+///
+/// [`LodEasing`]: crate::LodEasing
+#[derive(Clone, Copy, Debug, Component, ExtractComponent)]
+pub struct LodHysteresis(pub f32);
+
+impl LodHysteresis {
+    /// Returns the LOD selected given the LOD `previous` selected for the same view last frame,
+    /// along with `lod` clamped so that it geomorphs within the selected LOD.
+    pub(crate) fn apply(
+        &self,
+        lod: f32,
+        transition_width: f32,
+        previous: Option<u32>,
+    ) -> (u32, f32) {
+        let floor_lod = lod - transition_width;
+
+        let selected = match previous {
+            Some(previous)
+                if floor_lod >= previous as f32 - self.0
+                    && floor_lod < (previous + 1) as f32 + self.0 =>
+            {
+                previous
+            }
+            _ => floor_lod.floor().max(0.0) as u32,
+        };
+
+        // Keep the geomorph just short of completing the next transition, so that the selected LOD
+        // is the one that gets drawn.
+        let min_lod = selected as f32 + transition_width;
+        let max_lod = min_lod + 1.0 - 1e-4;
+
+        (selected, lod.clamp(min_lod, max_lod))
+    }
+}
+
+/// The LOD last selected for each view and [`LodHysteresis`] entity.
+#[derive(Resource)]
+pub struct RenderLodHysteresis<const U: usize, M: Material> {
+    pub(crate) lods: HashMap<(Entity, Entity), (u32, f32)>,
+    marker: PhantomData<M>,
+}
+
+impl<const U: usize, M: Material> Default for RenderLodHysteresis<U, M> {
+    fn default() -> Self {
+        Self {
+            lods: default(),
+            marker: PhantomData,
+        }
+    }
+}
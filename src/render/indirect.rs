@@ -0,0 +1,444 @@
+use std::{any::type_name, marker::PhantomData, mem};
+
+use bevy::{
+    ecs::system::SystemParam,
+    pbr::MeshUniform,
+    prelude::*,
+    render::{
+        extract_resource::ExtractResource,
+        mesh::GpuBufferInfo,
+        render_asset::RenderAssets,
+        render_graph::{Node, NodeRunError, RenderGraph, RenderGraphContext},
+        render_resource::{
+            BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout,
+            BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingType, Buffer,
+            BufferBindingType, BufferDescriptor, BufferUsages, CachedComputePipelineId,
+            ComputePassDescriptor, ComputePipelineDescriptor, PipelineCache, ShaderDefVal,
+            ShaderStages, ShaderType, StorageBuffer,
+        },
+        renderer::{RenderContext, RenderDevice, RenderQueue},
+        settings::WgpuFeatures,
+        view::{ExtractedView, VisibleEntities},
+    },
+    utils::HashMap,
+};
+
+use super::{
+    allocator::RenderLodMeshes,
+    focus::{GpuLodFocus, LodFocusUniform},
+    hysteresis::RenderLodHysteresis,
+    material::{lod_vectors, LodMaterial, RenderLodMaterials, WrappedMaterial},
+    LOD_INDIRECT_SHADER_HANDLE,
+};
+
+/// Selects how meshes drawn with a [`LodMaterial`] choose their LOD and submit their draws.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Resource, ExtractResource)]
+pub enum LodDrawMode {
+    /// Each mesh selects its LOD and issues its draws from the CPU.
+    #[default]
+    Direct,
+    /// A compute pass selects the LOD of every mesh and writes its draws, which are then issued
+    /// with multi-draw-indirect.
+    ///
+    /// Devices without multi-draw-indirect fall back to [`LodDrawMode::Direct`], as do meshes
+    /// without indices.
+    Indirect,
+}
+
+/// The number of indirect draws written for each mesh: one per LOD and face direction.
+pub(crate) const fn draws_per_mesh(lods: usize) -> usize {
+    lods * 6
+}
+
+const DRAW_INDEXED_INDIRECT_SIZE: u64 = 5 * mem::size_of::<u32>() as u64;
+
+const WORKGROUP_SIZE: u32 = 64;
+
+#[derive(Clone, Copy, ShaderType)]
+struct GpuLodView {
+    inverse_view: Mat4,
+    world_position: Vec3,
+    orthographic: u32,
+    forward: Vec3,
+    pixel_scale: f32,
+}
+
+#[derive(Clone, Copy, ShaderType)]
+struct GpuLodMesh {
+    model: Mat4,
+    inverse_model: Mat4,
+    size: UVec3,
+    max_lod: u32,
+    period: u32,
+    easing: u32,
+    pixel_tolerance: f32,
+    transition_width: f32,
+    view: u32,
+    /// The LOD selected on the CPU, or a negative value to select it in the compute pass.
+    fixed_lod: f32,
+    /// The offset of the mesh within its index buffer, in quads.
+    first_quad: u32,
+}
+
+/// The indirect draws of every visible mesh drawn with a [`LodMaterial`] with `U` LODs.
+#[derive(Resource)]
+pub struct RenderLodIndirect<const U: usize, M: Material> {
+    views: StorageBuffer<Vec<GpuLodView>>,
+    meshes: StorageBuffer<Vec<GpuLodMesh>>,
+    face_counts: StorageBuffer<Vec<u32>>,
+    lod_distances: StorageBuffer<Vec<f32>>,
+    pub(crate) draws: Option<Buffer>,
+    bind_group: Option<BindGroup>,
+    /// The index of the draws of each view and mesh within `draws`.
+    pub(crate) slots: HashMap<(Entity, Entity), u32>,
+    marker: PhantomData<M>,
+}
+
+impl<const U: usize, M: Material> Default for RenderLodIndirect<U, M> {
+    fn default() -> Self {
+        Self {
+            views: default(),
+            meshes: default(),
+            face_counts: default(),
+            lod_distances: default(),
+            draws: None,
+            bind_group: None,
+            slots: default(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<const U: usize, M: Material> RenderLodIndirect<U, M> {
+    /// The byte offset of the draws of the mesh at `slot`.
+    pub(crate) fn offset(slot: u32) -> u64 {
+        slot as u64 * draws_per_mesh(U) as u64 * DRAW_INDEXED_INDIRECT_SIZE
+    }
+}
+
+#[derive(Resource)]
+pub struct LodIndirectPipeline<const U: usize> {
+    layout: BindGroupLayout,
+    pipeline_id: CachedComputePipelineId,
+    /// Whether the device can issue the draws written by the compute pass.
+    supported: bool,
+}
+
+impl<const U: usize> FromWorld for LodIndirectPipeline<U> {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let supported = render_device
+            .features()
+            .contains(WgpuFeatures::MULTI_DRAW_INDIRECT | WgpuFeatures::INDIRECT_FIRST_INSTANCE);
+
+        let layout = render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("lod_indirect_bind_group_layout"),
+            entries: &[
+                storage_layout_entry(0, true),
+                storage_layout_entry(1, true),
+                storage_layout_entry(2, true),
+                storage_layout_entry(3, true),
+                BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: ShaderStages::COMPUTE,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: Some(GpuLodFocus::min_size()),
+                    },
+                    count: None,
+                },
+                storage_layout_entry(5, false),
+            ],
+        });
+
+        let pipeline_id =
+            world
+                .resource::<PipelineCache>()
+                .queue_compute_pipeline(ComputePipelineDescriptor {
+                    label: Some("lod_indirect_pipeline".into()),
+                    layout: vec![layout.clone()],
+                    push_constant_ranges: vec![],
+                    shader: LOD_INDIRECT_SHADER_HANDLE.typed(),
+                    shader_defs: vec![
+                        ShaderDefVal::UInt("LOD_COUNT".into(), U as u32),
+                        ShaderDefVal::UInt("LOD_VECTORS".into(), lod_vectors(U) as u32),
+                    ],
+                    entry_point: "write_draws".into(),
+                });
+
+        Self {
+            layout,
+            pipeline_id,
+            supported,
+        }
+    }
+}
+
+fn storage_layout_entry(binding: u32, read_only: bool) -> BindGroupLayoutEntry {
+    BindGroupLayoutEntry {
+        binding,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::Buffer {
+            ty: BufferBindingType::Storage { read_only },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }
+}
+
+type LodIndirectMeshQuery<'a, const U: usize> = (
+    &'a Handle<LodMaterial<U>>,
+    &'a Handle<Mesh>,
+    &'a MeshUniform,
+);
+
+/// The views, the meshes they see drawn with a [`LodMaterial`] and `M`, and what those meshes
+/// are drawn with.
+#[derive(SystemParam)]
+pub(crate) struct LodIndirectScene<'w, 's, const U: usize, M: Material> {
+    views: Query<'w, 's, (Entity, &'static ExtractedView, &'static VisibleEntities)>,
+    focus_uniform: Res<'w, LodFocusUniform>,
+    render_meshes: Res<'w, RenderAssets<Mesh>>,
+    render_lod_materials: Res<'w, RenderLodMaterials<U>>,
+    hysteresis: Res<'w, RenderLodHysteresis<U, M>>,
+    shared_meshes: Res<'w, RenderLodMeshes<U, M>>,
+    material_meshes: Query<'w, 's, LodIndirectMeshQuery<'static, U>, With<WrappedMaterial<M>>>,
+}
+
+/// Gathers the visible meshes of every view for the compute pass writing their draws.
+///
+/// This runs after `queue_lod_material_meshes`, so that LODs held by [`LodHysteresis`] are
+/// passed on rather than selected again.
+///
+/// [`LodHysteresis`]: crate::LodHysteresis
+pub(crate) fn queue_lod_indirect<const U: usize, M: Material>(
+    mode: Option<Res<LodDrawMode>>,
+    pipeline: Res<LodIndirectPipeline<U>>,
+    pipeline_cache: Res<PipelineCache>,
+    mut indirect: ResMut<RenderLodIndirect<U, M>>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    scene: LodIndirectScene<U, M>,
+) {
+    let indirect = &mut *indirect;
+
+    indirect.slots.clear();
+    indirect.bind_group = None;
+
+    // Until the compute pipeline is ready, meshes are drawn directly.
+    if mode.as_deref() != Some(&LodDrawMode::Indirect)
+        || !pipeline.supported
+        || pipeline_cache
+            .get_compute_pipeline(pipeline.pipeline_id)
+            .is_none()
+    {
+        return;
+    }
+
+    let gpu_views = indirect.views.get_mut();
+    let gpu_meshes = indirect.meshes.get_mut();
+    let face_counts = indirect.face_counts.get_mut();
+    let lod_distances = indirect.lod_distances.get_mut();
+
+    gpu_views.clear();
+    gpu_meshes.clear();
+    face_counts.clear();
+    lod_distances.clear();
+
+    for (view_entity, view, visible_entities) in &scene.views {
+        let view_index = gpu_views.len() as u32;
+        gpu_views.push(GpuLodView {
+            inverse_view: view.transform.compute_matrix().inverse(),
+            world_position: view.transform.translation(),
+            orthographic: (view.projection.w_axis.w == 1.0) as u32,
+            forward: view.transform.forward(),
+            pixel_scale: view.projection.y_axis.y * view.viewport.w as f32 / 2.0,
+        });
+
+        for visible_entity in &visible_entities.entities {
+            let Ok((material_handle, mesh_handle, mesh_uniform)) =
+                scene.material_meshes.get(*visible_entity)
+            else {
+                continue;
+            };
+
+            let (Some(mesh), Some(prepared_material)) = (
+                scene.render_meshes.get(mesh_handle),
+                scene.render_lod_materials.get(material_handle),
+            ) else {
+                continue;
+            };
+
+            let first_quad = match scene.shared_meshes.get(mesh_handle) {
+                Some(shared) => shared.first_quad,
+                None if matches!(mesh.buffer_info, GpuBufferInfo::Indexed { .. }) => 0,
+                None => continue,
+            };
+
+            let material = &prepared_material.material;
+            let fixed_lod = scene
+                .hysteresis
+                .lods
+                .get(&(view_entity, *visible_entity))
+                .map_or(-1.0, |&(_, lod)| lod);
+
+            indirect
+                .slots
+                .insert((view_entity, *visible_entity), gpu_meshes.len() as u32);
+            gpu_meshes.push(GpuLodMesh {
+                model: mesh_uniform.transform,
+                inverse_model: mesh_uniform.inverse_transpose_model.transpose(),
+                size: material.size,
+                max_lod: material.max_lod,
+                period: material.period,
+                easing: material.easing.id(),
+                pixel_tolerance: material.pixel_tolerance,
+                transition_width: material.transition.width,
+                view: view_index,
+                fixed_lod,
+                first_quad,
+            });
+            face_counts.extend(material.face_counts.iter().flatten());
+            // Each mesh takes the same `array<vec4<f32>, #{LOD_VECTORS}>` as the material uniform.
+            lod_distances.extend(material.lod_distances);
+            lod_distances.resize(gpu_meshes.len() * lod_vectors(U) * 4, 0.0);
+        }
+    }
+
+    if indirect.slots.is_empty() {
+        return;
+    }
+
+    indirect.views.write_buffer(&render_device, &render_queue);
+    indirect.meshes.write_buffer(&render_device, &render_queue);
+    indirect
+        .face_counts
+        .write_buffer(&render_device, &render_queue);
+    indirect
+        .lod_distances
+        .write_buffer(&render_device, &render_queue);
+
+    let draws_size = RenderLodIndirect::<U, M>::offset(indirect.slots.len() as u32);
+    if indirect
+        .draws
+        .as_ref()
+        .map_or(true, |draws| draws.size() < draws_size)
+    {
+        indirect.draws = Some(render_device.create_buffer(&BufferDescriptor {
+            label: Some("lod_indirect_draws_buffer"),
+            size: draws_size,
+            usage: BufferUsages::STORAGE | BufferUsages::INDIRECT,
+            mapped_at_creation: false,
+        }));
+    }
+
+    let (
+        Some(views_binding),
+        Some(meshes_binding),
+        Some(face_counts_binding),
+        Some(lod_distances_binding),
+        Some(focus_binding),
+        Some(draws),
+    ) = (
+        indirect.views.binding(),
+        indirect.meshes.binding(),
+        indirect.face_counts.binding(),
+        indirect.lod_distances.binding(),
+        scene.focus_uniform.buffer.binding(),
+        indirect.draws.as_ref(),
+    )
+    else {
+        indirect.slots.clear();
+        return;
+    };
+
+    let bind_group = render_device.create_bind_group(&BindGroupDescriptor {
+        label: Some("lod_indirect_bind_group"),
+        layout: &pipeline.layout,
+        entries: &[
+            BindGroupEntry {
+                binding: 0,
+                resource: views_binding,
+            },
+            BindGroupEntry {
+                binding: 1,
+                resource: meshes_binding,
+            },
+            BindGroupEntry {
+                binding: 2,
+                resource: face_counts_binding,
+            },
+            BindGroupEntry {
+                binding: 3,
+                resource: lod_distances_binding,
+            },
+            BindGroupEntry {
+                binding: 4,
+                resource: focus_binding,
+            },
+            BindGroupEntry {
+                binding: 5,
+                resource: draws.as_entire_binding(),
+            },
+        ],
+    });
+
+    indirect.bind_group = Some(bind_group);
+}
+
+/// Runs the compute pass writing the indirect draws, before any camera is rendered.
+pub struct LodIndirectNode<const U: usize, M: Material>(PhantomData<M>);
+
+impl<const U: usize, M: Material> Default for LodIndirectNode<U, M> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<const U: usize, M: Material> LodIndirectNode<U, M> {
+    pub(crate) fn add_to_graph(render_graph: &mut RenderGraph) {
+        let name = type_name::<Self>();
+
+        render_graph.add_node(name, Self::default());
+        render_graph.add_node_edge(name, bevy::render::main_graph::node::CAMERA_DRIVER);
+    }
+}
+
+impl<const U: usize, M: Material> Node for LodIndirectNode<U, M> {
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let indirect = world.resource::<RenderLodIndirect<U, M>>();
+        let pipeline = world.resource::<LodIndirectPipeline<U>>();
+
+        let (Some(bind_group), Some(compute_pipeline)) = (
+            &indirect.bind_group,
+            world
+                .resource::<PipelineCache>()
+                .get_compute_pipeline(pipeline.pipeline_id),
+        ) else {
+            return Ok(());
+        };
+
+        let meshes = indirect.slots.len() as u32;
+
+        let mut pass =
+            render_context
+                .command_encoder()
+                .begin_compute_pass(&ComputePassDescriptor {
+                    label: Some("lod_indirect_pass"),
+                });
+
+        pass.set_pipeline(compute_pipeline);
+        pass.set_bind_group(0, bind_group, &[]);
+        pass.dispatch_workgroups((meshes + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE, 1, 1);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,216 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+    utils::BoxedFuture,
+};
+
+use crate::{build_pop_mesh, ChunkShape, Mesher, PopBuffer, UnorientedQuad, VoxScene, VoxVoxel};
+
+use super::{
+    depth_offset::LodDepthOffset,
+    easing::LodEasing,
+    material::{LodMaterial, LodTransition},
+};
+
+/// The size of the chunks models are split into, including their padding.
+const PADDED_CHUNK_SIZE: u32 = 34;
+const CHUNK_SIZE: u32 = PADDED_CHUNK_SIZE - 2;
+
+/// Which algorithm [`LodVoxLoader`] meshes chunks with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LodVoxMeshing {
+    #[default]
+    Greedy,
+    VisibleFaces,
+}
+
+/// How [`LodVoxLoader`] meshes models and sets up the [`LodMaterial`] of each chunk.
+///
+/// Settings belong to the loader registered by [`LodVoxPlugin`], and the asset server uses a
+/// single loader per extension, so every `.vox` file is loaded with the same settings.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LodVoxSettings {
+    pub meshing: LodVoxMeshing,
+    /// The number of LODs drawn, at most `U`.
+    pub lods: usize,
+    /// The size of a voxel, applied by the transform of each chunk.
+    pub voxel_size: f32,
+    pub easing: LodEasing,
+    pub period: u32,
+    pub pixel_tolerance: f32,
+    pub transition: LodTransition,
+    pub depth_offset: LodDepthOffset,
+}
+
+impl Default for LodVoxSettings {
+    fn default() -> Self {
+        Self {
+            meshing: LodVoxMeshing::Greedy,
+            lods: usize::MAX,
+            voxel_size: 1.0,
+            easing: LodEasing::Sine,
+            period: CHUNK_SIZE * 8,
+            pixel_tolerance: 1.0,
+            transition: LodTransition {
+                width: 0.25,
+                depth_offset_scale: 0.02,
+            },
+            depth_offset: LodDepthOffset::Nudge,
+        }
+    }
+}
+
+/// A MagicaVoxel model loaded by [`LodVoxLoader`], split into chunks.
+///
+/// Each chunk is also a labeled asset: `model.vox#Chunk0` is the mesh of the first chunk and
+/// `model.vox#Chunk0/Material` its material, so a model that fits in one chunk can be used
+/// without this asset. Meshes have vertex colours from the palette of the model, which a
+/// [`WrappedMaterial`](super::material::WrappedMaterial) such as `StandardMaterial` applies.
+#[derive(TypePath, TypeUuid, Debug, Clone)]
+#[uuid = "2f4f1b0e-6a4e-4c1d-9a57-3c1f3e0f6b42"]
+pub struct LodVoxModel<const U: usize> {
+    pub chunks: Vec<LodVoxChunk<U>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LodVoxChunk<const U: usize> {
+    pub mesh: Handle<Mesh>,
+    pub material: Handle<LodMaterial<U>>,
+    /// Places the chunk within the model, scaled to the voxel size.
+    pub transform: Transform,
+}
+
+/// Loads `.vox` files into [`LodVoxModel`]s, converted to Y-up.
+///
+/// Files are reloaded when they change if the `AssetServer` watches for changes.
+pub struct LodVoxLoader<const U: usize> {
+    pub settings: LodVoxSettings,
+}
+
+impl<const U: usize> AssetLoader for LodVoxLoader<U> {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let scene = VoxScene::parse(bytes)?;
+            let model = scene.merge().to_y_up();
+
+            let mut mesher =
+                Mesher::<PADDED_CHUNK_SIZE, PADDED_CHUNK_SIZE, PADDED_CHUNK_SIZE, U>::new();
+            let mut chunks = Vec::new();
+
+            for (index, (chunk, voxels)) in model
+                .chunks::<PADDED_CHUNK_SIZE, PADDED_CHUNK_SIZE, PADDED_CHUNK_SIZE>()
+                .enumerate()
+            {
+                let settings = &self.settings;
+                let (face_counts, mesh) = match settings.meshing {
+                    LodVoxMeshing::Greedy => {
+                        build_chunk_mesh(mesher.mesh_greedy(&voxels), &voxels, &scene.palette)
+                    }
+                    LodVoxMeshing::VisibleFaces => build_chunk_mesh(
+                        mesher.mesh_visible_faces(&voxels),
+                        &voxels,
+                        &scene.palette,
+                    ),
+                };
+
+                let material = LodMaterial {
+                    size: UVec3::splat(CHUNK_SIZE),
+                    max_lod: settings.lods.min(U) as u32,
+                    period: settings.period,
+                    easing: settings.easing,
+                    face_counts,
+                    pixel_tolerance: settings.pixel_tolerance,
+                    lod_distances: [0.0; U],
+                    transition: settings.transition,
+                    depth_offset: settings.depth_offset,
+                };
+
+                let label = format!("Chunk{index}");
+                let material = load_context
+                    .set_labeled_asset(&format!("{label}/Material"), LoadedAsset::new(material));
+                let mesh = load_context.set_labeled_asset(&label, LoadedAsset::new(mesh));
+
+                // Chunks start one voxel before their first voxel, at the padding.
+                let voxel_size = settings.voxel_size;
+                let minimum = (chunk * CHUNK_SIZE).as_vec3() - Vec3::ONE;
+
+                chunks.push(LodVoxChunk {
+                    mesh,
+                    material,
+                    transform: Transform::from_translation(minimum * voxel_size)
+                        .with_scale(Vec3::splat(voxel_size)),
+                });
+            }
+
+            load_context.set_default_asset(LoadedAsset::new(LodVoxModel { chunks }));
+
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["vox"]
+    }
+}
+
+fn build_chunk_mesh<const U: usize, Q: Into<UnorientedQuad> + Clone>(
+    buffer: &PopBuffer<U, Q>,
+    voxels: &[VoxVoxel],
+    palette: &[[u8; 4]; 256],
+) -> ([[u32; 6]; U], Mesh) {
+    build_vox_mesh::<PADDED_CHUNK_SIZE, PADDED_CHUNK_SIZE, PADDED_CHUNK_SIZE, U, Q>(
+        buffer, voxels, palette,
+    )
+}
+
+/// Builds the mesh of a chunk of `X * Y * Z` voxels with [`build_pop_mesh`], coloured by the
+/// voxel behind each quad, along with its face counts.
+pub fn build_vox_mesh<const X: u32, const Y: u32, const Z: u32, const U: usize, Q>(
+    buffer: &PopBuffer<U, Q>,
+    voxels: &[VoxVoxel],
+    palette: &[[u8; 4]; 256],
+) -> ([[u32; 6]; U], Mesh)
+where
+    Q: Into<UnorientedQuad> + Clone,
+{
+    let mut mesh = build_pop_mesh(buffer);
+
+    let colors: Vec<[f32; 4]> = buffer
+        .quads()
+        .flat_map(|(_, quad)| {
+            let minimum = quad.clone().into().minimum;
+            let index = ChunkShape::<X, Y, Z>::linearize(minimum);
+            let color = voxels[index as usize].color(palette);
+
+            [Color::rgba_u8(color[0], color[1], color[2], color[3]).as_linear_rgba_f32(); 4]
+        })
+        .collect();
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+
+    (buffer.get_face_counts(), mesh)
+}
+
+/// Registers [`LodVoxLoader`] for [`LodMaterial`]s with `U` LODs.
+#[derive(Default)]
+pub struct LodVoxPlugin<const U: usize> {
+    pub settings: LodVoxSettings,
+}
+
+impl<const U: usize> Plugin for LodVoxPlugin<U> {
+    fn build(&self, app: &mut App) {
+        assert!(
+            U <= PADDED_CHUNK_SIZE.ilog2() as usize,
+            "LodVoxPlugin supports at most 5 LODs"
+        );
+
+        app.add_asset::<LodVoxModel<U>>()
+            .add_asset_loader(LodVoxLoader::<U> {
+                settings: self.settings,
+            });
+    }
+}
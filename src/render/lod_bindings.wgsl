@@ -10,4 +10,23 @@ var<uniform> max_lod: u32;
 var<uniform> period: u32;
 
 @group(3) @binding(3)
-var<uniform> buckets: array<vec4<u32>, 2>;
+var<uniform> pixel_tolerance: f32;
+
+struct LodTransition {
+    width: f32,
+    depth_offset_scale: f32,
+}
+
+@group(3) @binding(4)
+var<uniform> transition: LodTransition;
+
+@group(3) @binding(5)
+var<uniform> lod_distances: array<vec4<f32>, #{LOD_VECTORS}u>;
+
+struct LodFocus {
+    position: vec3<f32>,
+    enabled: u32,
+}
+
+@group(3) @binding(6)
+var<uniform> focus: LodFocus;
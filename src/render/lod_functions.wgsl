@@ -1,9 +1,7 @@
 #define_import_path bevy_mesh_pop::lod_functions
 
-#import bevy_pbr::mesh_functions as       mesh_functions
-#import bevy_pbr::mesh_view_bindings      view
-#import bevy_pbr::mesh_bindings           mesh
-#import bevy_mesh_pop::lod_bindings       size, max_lod, period, buckets
+// These functions take everything they need as arguments rather than reading bindings, so that
+// both the vertex shader and the compute pass writing indirect draws can import them.
 
 fn position_into_lod(index: u32, position: vec3<f32>, normal: vec3<f32>, lod: u32) -> vec3<f32> {
     let face = get_face(normal);
@@ -145,35 +143,105 @@ fn get_face(normal: vec3<f32>) -> Face {
   return face;
 }
 
-fn mesh_distance() -> f32 {
-    let world_position = mesh_functions::mesh_position_local_to_world(mesh.model, vec4<f32>(vec3<f32>(size) / 2.0, 1.0));
+// The on-screen height, in pixels, of a single voxel of a mesh centred at `world_position`, where
+// `pixel_scale` is the height of the viewport in pixels over that of the near plane.
+//
+// This mirrors `voxel_pixels` in `material.rs`.
+fn voxel_pixels(model: mat4x4<f32>, world_position: vec4<f32>, inverse_view: mat4x4<f32>, pixel_scale: f32, orthographic: bool) -> f32 {
+    let voxel_size = max(length(model[0].xyz), max(length(model[1].xyz), length(model[2].xyz)));
 
-    return length(world_position.xyz - view.world_position);
+    // orthographic projections do not shrink with depth
+    if orthographic {
+        return voxel_size * pixel_scale;
+    }
+
+    let view_position = inverse_view * world_position;
+
+    return voxel_size * pixel_scale / max(-view_position.z, 1.1920929e-7);
 }
 
 const pi = 3.14159265358979323846264338327950288;
 
-fn calculate_lod() -> f32 {
-    let distance = mesh_distance();
+// The indices of the variants of `LodEasing`, as returned by `LodEasing::id`.
+const EASING_LINEAR = 0u;
+const EASING_QUADRATIC = 1u;
+const EASING_CUBIC = 2u;
+const EASING_SINE = 3u;
+const EASING_SCREEN_SPACE = 4u;
+const EASING_PIECEWISE = 5u;
+
+// This mirrors `LodEasing::calculate` in `easing.rs`.
+fn ease_lod(easing: u32, distance: f32, voxel_pixels: f32, period: u32, max_lod: u32, pixel_tolerance: f32, lod_distances: array<vec4<f32>, #{LOD_VECTORS}u>) -> f32 {
     let clamped_distance = clamp(distance, 0.0, f32(period));
 
-#ifdef EASING_LINEAR
-    return f32(max_lod) / f32(period) * clamped_distance);
-#endif
+    if easing == EASING_LINEAR {
+        return f32(max_lod) / f32(period) * clamped_distance;
+    } else if easing == EASING_QUADRATIC {
+        return f32(max_lod) * pow(clamped_distance / f32(period), 2.0);
+    } else if easing == EASING_CUBIC {
+        return f32(max_lod) * pow(clamped_distance / f32(period), 3.0);
+    } else if easing == EASING_SINE {
+        return f32(max_lod) - f32(max_lod) * cos(pi * clamped_distance / (2.0 * f32(period)));
+    } else if easing == EASING_SCREEN_SPACE {
+        return clamp(log2(pixel_tolerance / voxel_pixels), 0.0, f32(max_lod));
+    }
 
-#ifdef EASING_QUADRATIC
-    return f32(max_lod) * pow(clamoed_distance / f32(period), 2.0);
-#endif
+    return piecewise_lod(distance, max_lod, lod_distances);
+}
+
+// This mirrors `piecewise_lod` in `easing.rs`.
+fn piecewise_lod(distance: f32, max_lod: u32, lod_distances: array<vec4<f32>, #{LOD_VECTORS}u>) -> f32 {
+    // arrays can only be indexed dynamically through a variable
+    var distances = lod_distances;
+    let last_lod = min(max_lod, #{LOD_COUNT}u - 1u);
+
+    var lod = 0u;
+    while lod < last_lod && distance >= distances[(lod + 1u) / 4u][(lod + 1u) % 4u] {
+        lod += 1u;
+    }
+
+    if lod == last_lod {
+        return f32(last_lod);
+    }
+
+    let start = distances[lod / 4u][lod % 4u];
+    let end = distances[(lod + 1u) / 4u][(lod + 1u) % 4u];
+
+    return max(f32(lod) + (distance - start) / (end - start), 0.0);
+}
+
+// Whether the faces of a mesh of `size` voxels at `face` in `OrientedBlockFace::FACES` can face
+// a view at `view_position` looking along `view_forward`, given the inverse of the transform of
+// the mesh.
+//
+// This mirrors `facing_faces` in `material.rs`.
+fn is_facing(face: u32, size: vec3<u32>, inverse_model: mat4x4<f32>, view_position: vec3<f32>, view_forward: vec3<f32>, orthographic: bool) -> bool {
+    let axis = face % 3u;
+    let is_front = face >= 3u;
+
+    if orthographic {
+        let forward = (inverse_model * vec4<f32>(view_forward, 0.0)).xyz;
+        return select(forward[axis] >= 0.0, forward[axis] <= 0.0, is_front);
+    }
+
+    let camera = (inverse_model * vec4<f32>(view_position, 1.0)).xyz;
+
+    if is_front {
+        return camera[axis] > 1.0;
+    }
+
+    return camera[axis] < f32(size[axis]) + 1.0;
+}
 
-#ifdef EASING_CUBIC
-    return f32(max_lod) * pow(clamped_distance / f32(period), 3.0);
-#endif
+// Set in the instance index of draws of quads that disappear at the end of the transition.
+const OUTGOING_INSTANCE: u32 = 0x80000000u;
 
-#ifdef EASING_SINE
-    return f32(max_lod) - f32(max_lod) * cos(pi * clamped_distance / (2.0 * f32(period)));
-#endif
+// LODs selected on the CPU are passed through the instance index as fixed point, offset by one,
+// so that zero leaves the LOD to be selected by the vertex shader.
+fn instance_lod(instance_index: u32) -> f32 {
+    return f32((instance_index & ~OUTGOING_INSTANCE) - 1u) / 65536.0;
 }
 
-fn lod_index(lod: u32) -> u32 {
-    return buckets[lod / 4u][lod % 4u];
+fn is_outgoing(instance_index: u32) -> bool {
+    return (instance_index & OUTGOING_INSTANCE) != 0u;
 }
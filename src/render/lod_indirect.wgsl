@@ -0,0 +1,115 @@
+#import bevy_mesh_pop::lod_functions as lod_functions
+
+struct LodView {
+    inverse_view: mat4x4<f32>,
+    world_position: vec3<f32>,
+    orthographic: u32,
+    forward: vec3<f32>,
+    pixel_scale: f32,
+}
+
+struct LodChunk {
+    model: mat4x4<f32>,
+    inverse_model: mat4x4<f32>,
+    size: vec3<u32>,
+    max_lod: u32,
+    period: u32,
+    easing: u32,
+    pixel_tolerance: f32,
+    transition_width: f32,
+    view: u32,
+    fixed_lod: f32,
+    first_quad: u32,
+}
+
+struct LodFocus {
+    position: vec3<f32>,
+    enabled: u32,
+}
+
+struct DrawIndexedIndirect {
+    index_count: u32,
+    instance_count: u32,
+    first_index: u32,
+    base_vertex: i32,
+    first_instance: u32,
+}
+
+@group(0) @binding(0)
+var<storage, read> views: array<LodView>;
+
+@group(0) @binding(1)
+var<storage, read> chunks: array<LodChunk>;
+
+@group(0) @binding(2)
+var<storage, read> face_counts: array<u32>;
+
+@group(0) @binding(3)
+var<storage, read> lod_distances: array<array<vec4<f32>, #{LOD_VECTORS}u>>;
+
+@group(0) @binding(4)
+var<uniform> focus: LodFocus;
+
+@group(0) @binding(5)
+var<storage, read_write> draws: array<DrawIndexedIndirect>;
+
+// This mirrors `calculate_lod` in `material.wgsl`, selecting the easing at runtime from
+// `LodEasing::id`.
+fn calculate_lod(chunk_index: u32, chunk: LodChunk, view: LodView) -> f32 {
+    let world_position = chunk.model * vec4<f32>(vec3<f32>(chunk.size) / 2.0, 1.0);
+
+    var focus_position = view.world_position;
+    if focus.enabled != 0u {
+        focus_position = focus.position;
+    }
+
+    let distance = length(world_position.xyz - focus_position);
+    let voxel_pixels = lod_functions::voxel_pixels(chunk.model, world_position, view.inverse_view, view.pixel_scale, view.orthographic != 0u);
+
+    return lod_functions::ease_lod(chunk.easing, distance, voxel_pixels, chunk.period, chunk.max_lod, chunk.pixel_tolerance, lod_distances[chunk_index]);
+}
+
+@compute @workgroup_size(64, 1, 1)
+fn write_draws(@builtin(global_invocation_id) id: vec3<u32>) {
+    let chunk_index = id.x;
+    if chunk_index >= arrayLength(&chunks) {
+        return;
+    }
+
+    let chunk = chunks[chunk_index];
+    let view = views[chunk.view];
+
+    var lod = chunk.fixed_lod;
+    if lod < 0.0 {
+        lod = calculate_lod(chunk_index, chunk, view);
+    }
+
+    // A LOD of `LOD_COUNT` draws the last group, even without a transition.
+    let floor_lod = min(u32(max(floor(lod - chunk.transition_width), 0.0)), #{LOD_COUNT}u - 1u);
+    let next_lod = max(min(u32(floor(lod)), #{LOD_COUNT}u - 1u), floor_lod);
+    let instance = 1u + u32(lod * 65536.0);
+
+    // Groups are laid out from the last LOD, each holding one range per face.
+    let first_draw = chunk_index * #{LOD_COUNT}u * 6u;
+    var first_quad = chunk.first_quad;
+
+    for (var group = 0u; group < #{LOD_COUNT}u; group++) {
+        let lod_group = #{LOD_COUNT}u - 1u - group;
+
+        for (var face = 0u; face < 6u; face++) {
+            let draw_index = first_draw + group * 6u + face;
+            let count = face_counts[chunk_index * #{LOD_COUNT}u * 6u + lod_group * 6u + face];
+            let is_drawn = lod_group >= floor_lod && lod_functions::is_facing(face, chunk.size, chunk.inverse_model, view.world_position, view.forward, view.orthographic != 0u);
+
+            var draw: DrawIndexedIndirect;
+            draw.index_count = select(0u, count * 6u, is_drawn);
+            draw.instance_count = 1u;
+            draw.first_index = first_quad * 6u;
+            draw.base_vertex = i32(chunk.first_quad * 4u);
+            draw.first_instance = select(instance, instance | lod_functions::OUTGOING_INSTANCE, lod_group < next_lod);
+            draws[draw_index] = draw;
+
+            first_quad += count;
+        }
+    }
+}
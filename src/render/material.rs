@@ -1,7 +1,9 @@
 use std::{
     hash::{Hash, Hasher},
+    iter,
     marker::PhantomData,
     mem,
+    ops::Range,
 };
 
 use bevy::{
@@ -10,7 +12,7 @@ use bevy::{
         query::ROQueryItem,
         system::{
             lifetimeless::{Read, SRes},
-            SystemParamItem,
+            SystemParam, SystemParamItem,
         },
     },
     pbr::{
@@ -22,27 +24,55 @@ use bevy::{
     render::{
         extract_component::{ExtractComponent, ExtractComponentPlugin},
         mesh::{GpuBufferInfo, MeshVertexBufferLayout},
+        primitives::Aabb,
         render_asset::RenderAssets,
         render_phase::{
             AddRenderCommand, DrawFunctions, PhaseItem, RenderCommand, RenderCommandResult,
             RenderPhase, SetItemPipeline, TrackedRenderPass,
         },
         render_resource::{
-            AsBindGroup, AsBindGroupError, BindGroup, BindGroupLayout, OwnedBindingResource,
-            PipelineCache, RenderPipelineDescriptor, SpecializedMeshPipeline,
-            SpecializedMeshPipelineError, SpecializedMeshPipelines,
+            AsBindGroup, AsBindGroupError, BindGroup, BindGroupDescriptor, BindGroupEntry,
+            BindGroupLayout, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingType, Buffer,
+            BufferBindingType, BufferInitDescriptor, BufferSize, BufferUsages, IndexFormat,
+            OwnedBindingResource, PipelineCache, RenderPipelineDescriptor, ShaderDefVal,
+            ShaderStages, ShaderType, SpecializedMeshPipeline, SpecializedMeshPipelineError,
+            SpecializedMeshPipelines,
         },
         renderer::RenderDevice,
         texture::FallbackImage,
-        view::{ExtractedView, VisibleEntities},
+        view::{calculate_bounds, ExtractedView, VisibilitySystems, VisibleEntities},
         Extract, Render, RenderApp, RenderSet,
     },
     utils::{HashMap, HashSet},
 };
 use bevy_math::Vec4Swizzles;
+use bytemuck::Pod;
+
+use crate::OrientedBlockFace;
+
+use super::{
+    allocator::{extract_lod_meshes, prepare_lod_meshes, ExtractedLodMeshes, RenderLodMeshes},
+    bounds::update_lod_aabbs,
+    depth_offset::LodDepthOffset,
+    easing::LodEasing,
+    focus::{ExtractedLodFocus, GpuLodFocus, LodFocusUniform},
+    hysteresis::{LodHysteresis, RenderLodHysteresis},
+    indirect::{
+        draws_per_mesh, queue_lod_indirect, LodIndirectNode, LodIndirectPipeline, RenderLodIndirect,
+    },
+    LOD_MATERIAL_SHADER_HANDLE,
+};
 
-use super::{easing::LodEasing, LOD_MATERIAL_SHADER_HANDLE};
+/// The most LODs the shaders can address, since they shift by the LOD.
+pub const MAX_LODS: usize = u32::BITS as usize;
 
+/// A material drawing a mesh built from a [`PopBuffer`](crate::PopBuffer) with `U` LODs.
+///
+/// Meshes must be indexed, with four vertices per quad as from
+/// [`build_pop_mesh`](crate::build_pop_mesh); non-indexed meshes aren't drawn.
+///
+/// The per-LOD arrays are not part of the `AsBindGroup` bindings, since uniform arrays must be
+/// packed into vectors; they are bound after them by [`LodMaterialPlugin`].
 #[derive(AsBindGroup, TypePath, Debug, Clone, TypeUuid)]
 #[uuid = "8dba752b-f8a1-47ba-8d11-b569ca74526f"]
 #[bind_group_data(LodMaterialKey)]
@@ -54,8 +84,52 @@ pub struct LodMaterial<const U: usize> {
     #[uniform(2)]
     pub period: u32,
     pub easing: LodEasing,
+    /// The number of quads added at each LOD for each face direction, as returned by
+    /// [`PopBuffer::get_face_counts`](crate::PopBuffer::get_face_counts). Only the directions
+    /// that can face the camera are drawn.
+    pub face_counts: [[u32; 6]; U],
+    /// The largest on-screen size, in pixels, of a quantisation step when using
+    /// [`LodEasing::ScreenSpace`].
     #[uniform(3)]
-    pub buckets: [UVec4; 2],
+    pub pixel_tolerance: f32,
+    /// The distance at which each LOD starts when using [`LodEasing::Piecewise`], which tops out
+    /// at LOD `U - 1`. Distances must be increasing up to `max_lod.min(U - 1)`.
+    pub lod_distances: [f32; U],
+    #[uniform(4)]
+    pub transition: LodTransition,
+    pub depth_offset: LodDepthOffset,
+}
+
+impl<const U: usize> LodMaterial<U> {
+    const VALID_LODS: () = assert!(
+        U > 0 && U <= MAX_LODS,
+        "LodMaterial must have between 1 and MAX_LODS (32) LODs"
+    );
+
+    /// A bounding box covering the mesh at every LOD up to `max_lod`.
+    ///
+    /// Coarser LODs snap faces outward to multiples of their size, which can extend past the
+    /// voxels of the mesh. The size of LODs past 31 saturates at `2^31`.
+    pub fn aabb(&self) -> Aabb {
+        let lod_size = 1u32.checked_shl(self.max_lod).unwrap_or(1 << 31);
+        let maximum = self.size.to_array().map(|size| {
+            (size / lod_size + u32::from(size % lod_size != 0))
+                .saturating_mul(lod_size)
+                .saturating_add(1)
+        });
+
+        Aabb::from_min_max(Vec3::ONE, UVec3::from_array(maximum).as_vec3())
+    }
+}
+
+/// How a [`LodMaterial`] moves between LODs, bound as `transition` in the shaders.
+#[derive(ShaderType, Clone, Copy, Debug, PartialEq)]
+pub struct LodTransition {
+    /// The fraction of each LOD over which the previous LOD is geomorphed into it, from `0.0`,
+    /// which switches LODs without geomorphing, up to `1.0`.
+    pub width: f32,
+    /// The strength of the [`LodDepthOffset`] of the material; see it for its meaning.
+    pub depth_offset_scale: f32,
 }
 
 #[derive(Clone, Component, Deref, ExtractComponent)]
@@ -69,21 +143,22 @@ impl<M: Material> From<Handle<M>> for WrappedMaterial<M> {
 
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub struct LodMaterialKey {
-    size: UVec3,
     easing: LodEasing,
-    max_lod: u32,
-    period: u32,
-    buckets: [UVec4; 2],
+    depth_offset: LodDepthOffset,
+    /// The bits of `depth_offset_scale`, which [`LodDepthOffset::DepthBias`] specializes the
+    /// pipeline with.
+    depth_offset_scale: u32,
 }
 
 impl<const U: usize> From<&LodMaterial<U>> for LodMaterialKey {
     fn from(value: &LodMaterial<U>) -> Self {
         Self {
-            size: value.size,
             easing: value.easing,
-            max_lod: value.max_lod,
-            period: value.period,
-            buckets: value.buckets,
+            depth_offset: value.depth_offset,
+            depth_offset_scale: match value.depth_offset {
+                LodDepthOffset::DepthBias => value.transition.depth_offset_scale.to_bits(),
+                _ => 0,
+            },
         }
     }
 }
@@ -96,31 +171,55 @@ where
     M::Data: Clone + PartialEq + Eq + Hash,
 {
     fn build(&self, app: &mut App) {
-        app.add_asset::<LodMaterial<U>>().add_plugins((
-            ExtractComponentPlugin::<Handle<LodMaterial<U>>>::extract_visible(),
-            ExtractComponentPlugin::<WrappedMaterial<M>>::extract_visible(),
-        ));
+        let () = LodMaterial::<U>::VALID_LODS;
+
+        app.add_asset::<LodMaterial<U>>()
+            .add_plugins((
+                ExtractComponentPlugin::<Handle<LodMaterial<U>>>::extract_visible(),
+                ExtractComponentPlugin::<WrappedMaterial<M>>::extract_visible(),
+            ))
+            .add_systems(
+                PostUpdate,
+                update_lod_aabbs::<U>
+                    .in_set(VisibilitySystems::CalculateBounds)
+                    .after(calculate_bounds),
+            );
 
         if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
             render_app
                 .add_render_command::<Transparent3d, DrawLodMaterial<U, M>>()
                 .init_resource::<ExtractedLodMaterials<U>>()
                 .init_resource::<RenderLodMaterials<U>>()
+                .init_resource::<RenderLodHysteresis<U, M>>()
+                .init_resource::<RenderLodIndirect<U, M>>()
+                .init_resource::<ExtractedLodMeshes<U, M>>()
+                .init_resource::<RenderLodMeshes<U, M>>()
                 .init_resource::<SpecializedMeshPipelines<LodMaterialPipeline<U, M>>>()
-                .add_systems(ExtractSchedule, extract_lod_materials::<U>)
+                .add_systems(
+                    ExtractSchedule,
+                    (extract_lod_materials::<U>, extract_lod_meshes::<U, M>),
+                )
                 .add_systems(
                     Render,
                     (
                         prepare_lod_materials::<U, M>.in_set(RenderSet::Prepare),
+                        prepare_lod_meshes::<U, M>.in_set(RenderSet::Prepare),
                         queue_lod_material_meshes::<U, M>.in_set(RenderSet::Queue),
+                        queue_lod_indirect::<U, M>
+                            .in_set(RenderSet::Queue)
+                            .after(queue_lod_material_meshes::<U, M>),
                     ),
                 );
+
+            LodIndirectNode::<U, M>::add_to_graph(&mut render_app.world.resource_mut());
         }
     }
 
     fn finish(&self, app: &mut App) {
         if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
-            render_app.init_resource::<LodMaterialPipeline<U, M>>();
+            render_app
+                .init_resource::<LodMaterialPipeline<U, M>>()
+                .init_resource::<LodIndirectPipeline<U>>();
         }
     }
 }
@@ -166,6 +265,7 @@ where
 #[derive(Resource)]
 pub struct LodMaterialPipeline<const U: usize, M: Material> {
     material_pipeline: MaterialPipeline<M>,
+    material_layout: BindGroupLayout,
     lod_layout: BindGroupLayout,
     vertex_shader: Handle<Shader>,
     fragment_shader: Handle<Shader>,
@@ -190,6 +290,23 @@ where
             .vertex
             .shader_defs
             .push(key.bind_group_data.easing.into());
+        descriptor
+            .vertex
+            .shader_defs
+            .push(key.bind_group_data.depth_offset.into());
+        descriptor.vertex.shader_defs.extend([
+            ShaderDefVal::UInt("LOD_COUNT".into(), U as u32),
+            ShaderDefVal::UInt("LOD_VECTORS".into(), lod_vectors(U) as u32),
+        ]);
+
+        if let (Some(bias), Some(depth_stencil)) = (
+            key.bind_group_data
+                .depth_offset
+                .depth_bias(f32::from_bits(key.bind_group_data.depth_offset_scale)),
+            descriptor.depth_stencil.as_mut(),
+        ) {
+            depth_stencil.bias = bias;
+        }
 
         // TODO: move this to a bind command
         descriptor.layout.insert(3, self.lod_layout.clone());
@@ -211,13 +328,70 @@ impl<const U: usize, M: Material> FromWorld for LodMaterialPipeline<U, M> {
 
         Self {
             material_pipeline: world.resource::<MaterialPipeline<M>>().clone(),
-            lod_layout: LodMaterial::<U>::bind_group_layout(render_device),
+            material_layout: LodMaterial::<U>::bind_group_layout(render_device),
+            lod_layout: lod_bind_group_layout::<U>(render_device),
             vertex_shader: LOD_MATERIAL_SHADER_HANDLE.typed(),
             fragment_shader: PBR_SHADER_HANDLE.typed(),
         }
     }
 }
 
+/// The layout of the LOD bind group: the bindings of [`LodMaterial`], followed by its per-LOD
+/// arrays and the [`LodFocusUniform`] shared by every material.
+fn lod_bind_group_layout<const U: usize>(render_device: &RenderDevice) -> BindGroupLayout {
+    let lod_array_size = BufferSize::new((lod_vectors(U) * mem::size_of::<UVec4>()) as u64);
+
+    render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+        label: Some("lod_bind_group_layout"),
+        entries: &[
+            uniform_layout_entry(0, Some(UVec3::min_size())),
+            uniform_layout_entry(1, Some(u32::min_size())),
+            uniform_layout_entry(2, Some(u32::min_size())),
+            uniform_layout_entry(3, Some(f32::min_size())),
+            uniform_layout_entry(4, Some(LodTransition::min_size())),
+            uniform_layout_entry(5, lod_array_size),
+            uniform_layout_entry(6, Some(GpuLodFocus::min_size())),
+        ],
+    })
+}
+
+fn uniform_layout_entry(
+    binding: u32,
+    min_binding_size: Option<BufferSize>,
+) -> BindGroupLayoutEntry {
+    BindGroupLayoutEntry {
+        binding,
+        visibility: ShaderStages::all(),
+        ty: BindingType::Buffer {
+            ty: BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size,
+        },
+        count: None,
+    }
+}
+
+/// The number of vectors needed to hold a per-LOD array in a uniform, where array elements are
+/// aligned to 16 bytes.
+pub(crate) const fn lod_vectors(lods: usize) -> usize {
+    (lods + 3) / 4
+}
+
+/// Packs a per-LOD array into `array<vec4<T>, #{LOD_VECTORS}>`.
+fn lod_array_buffer<T: Pod, const U: usize>(
+    render_device: &RenderDevice,
+    values: &[T; U],
+) -> Buffer {
+    let mut contents = vec![T::zeroed(); lod_vectors(U) * 4];
+    contents[..U].copy_from_slice(values);
+
+    render_device.create_buffer_with_data(&BufferInitDescriptor {
+        label: None,
+        usage: BufferUsages::COPY_DST | BufferUsages::UNIFORM,
+        contents: bytemuck::cast_slice(&contents),
+    })
+}
+
 type DrawLodMaterial<const U: usize, M> = (
     SetItemPipeline,
     SetMeshViewBindGroup<0>,
@@ -253,8 +427,15 @@ impl<P: PhaseItem, const U: usize, M: Material, const I: usize> RenderCommand<P>
 pub struct DrawMeshLod<const U: usize, M: Material>(PhantomData<M>);
 
 impl<P: PhaseItem, const U: usize, M: Material> RenderCommand<P> for DrawMeshLod<U, M> {
-    type Param = (SRes<RenderAssets<Mesh>>, SRes<RenderLodMaterials<U>>);
-    type ViewWorldQuery = Read<ExtractedView>;
+    type Param = (
+        SRes<RenderAssets<Mesh>>,
+        SRes<RenderLodMaterials<U>>,
+        SRes<ExtractedLodFocus>,
+        SRes<RenderLodHysteresis<U, M>>,
+        SRes<RenderLodIndirect<U, M>>,
+        SRes<RenderLodMeshes<U, M>>,
+    );
+    type ViewWorldQuery = (Entity, Read<ExtractedView>);
     type ItemWorldQuery = (
         Read<MeshUniform>,
         Read<Handle<Mesh>>,
@@ -263,10 +444,14 @@ impl<P: PhaseItem, const U: usize, M: Material> RenderCommand<P> for DrawMeshLod
 
     #[inline]
     fn render<'w>(
-        _item: &P,
-        view: ROQueryItem<'w, Self::ViewWorldQuery>,
+        item: &P,
+        (view_entity, view): ROQueryItem<'w, Self::ViewWorldQuery>,
         (mesh_uniform, mesh_handle, material_handle): ROQueryItem<'w, Self::ItemWorldQuery>,
-        (meshes, materials): SystemParamItem<'w, '_, Self::Param>,
+        (meshes, materials, focus, hysteresis, indirect, shared): SystemParamItem<
+            'w,
+            '_,
+            Self::Param,
+        >,
         pass: &mut TrackedRenderPass<'w>,
     ) -> RenderCommandResult {
         let prepared_material = match materials.into_inner().get(material_handle) {
@@ -278,84 +463,279 @@ impl<P: PhaseItem, const U: usize, M: Material> RenderCommand<P> for DrawMeshLod
             None => return RenderCommandResult::Failure,
         };
 
-        let world_position =
-            mesh_uniform.transform * (prepared_material.key.size.as_vec3() / 2.0).extend(1.0);
+        pass.set_bind_group(3, &prepared_material.bind_group, &[]);
 
-        let distance = (world_position.xyz() - view.transform.translation()).length();
+        // Shared meshes are drawn from offsets into the shared buffers, so that binding them
+        // again for the next mesh is skipped.
+        let first_quad = match shared.into_inner().get(mesh_handle) {
+            Some(shared) => {
+                pass.set_vertex_buffer(0, shared.vertex_buffer);
+                pass.set_index_buffer(shared.index_buffer, 0, IndexFormat::Uint32);
+                shared.first_quad
+            }
+            None => {
+                pass.set_vertex_buffer(0, gpu_mesh.vertex_buffer.slice(..));
+                match &gpu_mesh.buffer_info {
+                    GpuBufferInfo::Indexed {
+                        buffer,
+                        count: _,
+                        index_format,
+                    } => {
+                        pass.set_index_buffer(buffer.slice(..), 0, *index_format);
+                        0
+                    }
+                    // The vertex shader finds the corners of quads from their four vertices.
+                    GpuBufferInfo::NonIndexed => return RenderCommandResult::Failure,
+                }
+            }
+        };
 
-        let lod = prepared_material.key.easing.calculate(
-            distance,
-            prepared_material.key.period,
-            prepared_material.key.max_lod,
-        ) - 0.25;
+        let indirect = indirect.into_inner();
+        if let (Some(&slot), Some(draws)) = (
+            indirect.slots.get(&(view_entity, item.entity())),
+            &indirect.draws,
+        ) {
+            pass.multi_draw_indexed_indirect(
+                draws,
+                RenderLodIndirect::<U, M>::offset(slot),
+                draws_per_mesh(U) as u32,
+            );
 
-        let floor_lod = lod.floor() as usize;
+            return RenderCommandResult::Success;
+        }
 
-        let end_index = prepared_material.key.buckets[floor_lod / 4][floor_lod % 4] as u32;
+        let material = &prepared_material.material;
 
-        pass.set_bind_group(3, &prepared_material.bind_group, &[]);
+        let selected_lod = hysteresis
+            .into_inner()
+            .lods
+            .get(&(view_entity, item.entity()));
 
-        pass.set_vertex_buffer(0, gpu_mesh.vertex_buffer.slice(..));
-
-        match &gpu_mesh.buffer_info {
-            GpuBufferInfo::Indexed {
-                buffer,
-                count: _,
-                index_format,
-            } => {
-                // let end_index = prepared_material.key.end_index * 6;
-                pass.set_index_buffer(buffer.slice(..), 0, *index_format);
-                pass.draw_indexed(0..(end_index * 6), 0, 0..1);
-            }
-            GpuBufferInfo::NonIndexed => {
-                // let end_index = prepared_material.key.end_index;
-                pass.draw(0..end_index, 0..1);
-            }
+        let (lod, instance) = match selected_lod {
+            Some(&(_, lod)) => (lod, 1 + (lod * LOD_INSTANCE_SCALE) as u32),
+            None => (
+                calculate_lod(material, &mesh_uniform.transform, view, &focus),
+                0,
+            ),
+        };
+
+        // A LOD of `U` draws the last group, even without a transition.
+        let floor_lod = ((lod - material.transition.width).floor() as usize).min(U - 1);
+        let next_lod = (lod.floor() as usize).min(U - 1).max(floor_lod);
+
+        let facing = facing_faces(
+            material.size,
+            &mesh_uniform.inverse_transpose_model.transpose(),
+            view,
+        );
+
+        // The quads that only exist before the transition are drawn separately, so that the shader
+        // can tell them apart.
+        let draws = face_ranges(&material.face_counts, next_lod..U, facing)
+            .map(|range| (range, instance))
+            .chain(
+                face_ranges(&material.face_counts, floor_lod..next_lod, facing)
+                    .map(|range| (range, instance | OUTGOING_INSTANCE)),
+            );
+
+        for (range, instance) in draws {
+            pass.draw_indexed(
+                ((first_quad + range.start) * 6)..((first_quad + range.end) * 6),
+                (first_quad * 4) as i32,
+                instance..(instance + 1),
+            );
         }
 
         RenderCommandResult::Success
     }
 }
 
+/// Which of the [`OrientedBlockFace::FACES`] directions of a mesh can face the view, given the
+/// inverse of its transform.
+fn facing_faces(size: UVec3, inverse_model: &Mat4, view: &ExtractedView) -> [bool; 6] {
+    // Orthographic projections see the same directions from anywhere.
+    if view.projection.w_axis.w == 1.0 {
+        let forward = (*inverse_model * view.transform.forward().extend(0.0)).xyz();
+        return OrientedBlockFace::FACES.map(|face| face.signed_n.as_vec3().dot(forward) <= 0.0);
+    }
+
+    let camera = (*inverse_model * view.transform.translation().extend(1.0)).xyz();
+
+    // Faces lie between the padding on either side of the mesh at every LOD, so a direction can
+    // only face the camera if the camera is in front of the furthest plane in that direction.
+    let min = Vec3::ONE;
+    let max = size.as_vec3() + Vec3::ONE;
+
+    OrientedBlockFace::FACES.map(|face| {
+        let n = face.n.as_vec3();
+        if face.is_front {
+            camera.dot(n) > min.dot(n)
+        } else {
+            camera.dot(n) < max.dot(n)
+        }
+    })
+}
+
+/// The ranges of quads in the groups of `lods` with one of the `facing` directions, merging
+/// directions that are adjacent in the mesh.
+fn face_ranges<const U: usize>(
+    face_counts: &[[u32; 6]; U],
+    lods: Range<usize>,
+    facing: [bool; 6],
+) -> impl Iterator<Item = Range<u32>> + '_ {
+    let mut start = 0;
+    let mut ranges = face_counts
+        .iter()
+        .enumerate()
+        .rev()
+        .flat_map(move |(lod, counts)| {
+            let is_drawn = lods.contains(&lod);
+            counts
+                .iter()
+                .zip(facing)
+                .map(move |(&count, facing)| (count, is_drawn && facing))
+        })
+        .filter_map(move |(count, is_drawn)| {
+            let range = start..(start + count);
+            start += count;
+            (is_drawn && count > 0).then_some(range)
+        })
+        .peekable();
+
+    iter::from_fn(move || {
+        let mut range = ranges.next()?;
+        while let Some(next) = ranges.next_if(|next| next.start == range.end) {
+            range.end = next.end;
+        }
+        Some(range)
+    })
+}
+
+/// LODs selected on the CPU are passed to the vertex shader through the instance index as fixed
+/// point, offset by one so that an instance index of zero leaves the shader to select the LOD.
+const LOD_INSTANCE_SCALE: f32 = 65536.0;
+
+/// Set in the instance index of draws of quads that disappear at the end of the transition, which
+/// the vertex shader offsets in depth.
+const OUTGOING_INSTANCE: u32 = 1 << 31;
+
+/// This mirrors `calculate_lod` in `material.wgsl`.
+fn calculate_lod<const U: usize>(
+    material: &LodMaterial<U>,
+    mesh_transform: &Mat4,
+    view: &ExtractedView,
+    focus: &ExtractedLodFocus,
+) -> f32 {
+    let world_position = *mesh_transform * (material.size.as_vec3() / 2.0).extend(1.0);
+
+    let focus_position = focus.0.unwrap_or_else(|| view.transform.translation());
+    let distance = (world_position.xyz() - focus_position).length();
+
+    material.easing.calculate(
+        distance,
+        voxel_pixels(mesh_transform, world_position.xyz(), view),
+        material.period,
+        material.max_lod,
+        material.pixel_tolerance,
+        &material.lod_distances,
+    )
+}
+
+/// The on-screen height, in pixels, of a single voxel of a mesh centred at `world_position`.
+///
+/// This mirrors `voxel_pixels` in `lod_functions.wgsl`.
+fn voxel_pixels(mesh_transform: &Mat4, world_position: Vec3, view: &ExtractedView) -> f32 {
+    let voxel_size = mesh_transform
+        .x_axis
+        .xyz()
+        .length()
+        .max(mesh_transform.y_axis.xyz().length())
+        .max(mesh_transform.z_axis.xyz().length());
+    let scale = view.projection.y_axis.y * view.viewport.w as f32 / 2.0;
+
+    // Orthographic projections do not shrink with depth.
+    if view.projection.w_axis.w == 1.0 {
+        return voxel_size * scale;
+    }
+
+    // Using the view-space depth rather than the distance accounts for chunks far off-axis
+    // projecting larger than chunks at the same distance in the centre of the view.
+    let view_position = view.transform.compute_matrix().inverse() * world_position.extend(1.0);
+    voxel_size * scale / (-view_position.z).max(f32::EPSILON)
+}
+
+/// The pipelines meshes drawn with a [`LodMaterial`] and `M` are specialized into.
+#[derive(SystemParam)]
+pub struct LodMaterialPipelines<'w, const U: usize, M: Material>
+where
+    M::Data: Clone + PartialEq + Eq + Hash,
+{
+    draw_functions: Res<'w, DrawFunctions<Transparent3d>>,
+    lod_pipeline: Res<'w, LodMaterialPipeline<U, M>>,
+    pipelines: ResMut<'w, SpecializedMeshPipelines<LodMaterialPipeline<U, M>>>,
+    pipeline_cache: Res<'w, PipelineCache>,
+    msaa: Res<'w, Msaa>,
+}
+
+/// The prepared assets meshes drawn with a [`LodMaterial`] and `M` are drawn with.
+#[derive(SystemParam)]
+pub struct LodMaterialAssets<'w, const U: usize, M: Material> {
+    meshes: Res<'w, RenderAssets<Mesh>>,
+    materials: Res<'w, RenderMaterials<M>>,
+    lod_materials: Res<'w, RenderLodMaterials<U>>,
+}
+
+type LodMaterialMeshQuery<'a, const U: usize, M> = (
+    &'a WrappedMaterial<M>,
+    &'a Handle<LodMaterial<U>>,
+    &'a Handle<Mesh>,
+    &'a MeshUniform,
+    Option<&'a LodHysteresis>,
+);
+
+type LodMaterialViewQuery<'a> = (
+    Entity,
+    &'a ExtractedView,
+    &'a VisibleEntities,
+    &'a mut RenderPhase<Transparent3d>,
+);
+
 pub fn queue_lod_material_meshes<const U: usize, M: Material>(
-    transparent_draw_functions: Res<DrawFunctions<Transparent3d>>,
-    lod_pipeline: Res<LodMaterialPipeline<U, M>>,
-    mut pipelines: ResMut<SpecializedMeshPipelines<LodMaterialPipeline<U, M>>>,
-    pipeline_cache: Res<PipelineCache>,
-    msaa: Res<Msaa>,
-    render_meshes: Res<RenderAssets<Mesh>>,
-    render_materials: Res<RenderMaterials<M>>,
-    render_lod_materials: Res<RenderLodMaterials<U>>,
-    material_meshes: Query<(
-        &WrappedMaterial<M>,
-        &Handle<LodMaterial<U>>,
-        &Handle<Mesh>,
-        &MeshUniform,
-    )>,
-    mut views: Query<(
-        &ExtractedView,
-        &VisibleEntities,
-        &mut RenderPhase<Transparent3d>,
-    )>,
+    mut pipelines: LodMaterialPipelines<U, M>,
+    assets: LodMaterialAssets<U, M>,
+    focus: Res<ExtractedLodFocus>,
+    mut hysteresis: ResMut<RenderLodHysteresis<U, M>>,
+    material_meshes: Query<LodMaterialMeshQuery<U, M>>,
+    mut views: Query<LodMaterialViewQuery>,
 ) where
     M::Data: Clone + PartialEq + Eq + Hash,
 {
-    for (view, visible_entities, mut transparent_phase) in &mut views {
-        let draw_lod = transparent_draw_functions
+    // Entries for meshes that are no longer visible are dropped, so they start from scratch when
+    // they become visible again.
+    let previous_lods = mem::take(&mut hysteresis.lods);
+
+    for (view_entity, view, visible_entities, mut transparent_phase) in &mut views {
+        let draw_lod = pipelines
+            .draw_functions
             .read()
             .id::<DrawLodMaterial<U, M>>();
-        let msaa_key = MeshPipelineKey::from_msaa_samples(msaa.samples());
+        let msaa_key = MeshPipelineKey::from_msaa_samples(pipelines.msaa.samples());
         let view_key = MeshPipelineKey::from_hdr(view.hdr) | msaa_key;
 
         let rangefinder = view.rangefinder3d();
         for visible_entity in &visible_entities.entities {
-            if let Ok((wrapped_material_handle, lod_material_handle, mesh_handle, mesh_uniform)) =
-                material_meshes.get(*visible_entity)
+            if let Ok((
+                wrapped_material_handle,
+                lod_material_handle,
+                mesh_handle,
+                mesh_uniform,
+                mesh_hysteresis,
+            )) = material_meshes.get(*visible_entity)
             {
                 if let (Some(mesh), Some(lod_material), Some(material)) = (
-                    render_meshes.get(mesh_handle),
-                    render_lod_materials.get(lod_material_handle),
-                    render_materials.get(&wrapped_material_handle.0),
+                    assets.meshes.get(mesh_handle),
+                    assets.lod_materials.get(lod_material_handle),
+                    assets.materials.get(&wrapped_material_handle.0),
                 ) {
                     let mesh_key = view_key
                         | MeshPipelineKey::from_primitive_topology(mesh.primitive_topology);
@@ -368,8 +748,35 @@ pub fn queue_lod_material_meshes<const U: usize, M: Material>(
                         bind_group_data: lod_material.key.clone(),
                     };
 
+                    if let Some(mesh_hysteresis) = mesh_hysteresis {
+                        let lod = calculate_lod(
+                            &lod_material.material,
+                            &mesh_uniform.transform,
+                            view,
+                            &focus,
+                        );
+                        let previous = previous_lods
+                            .get(&(view_entity, *visible_entity))
+                            .map(|&(selected, _)| selected);
+
+                        hysteresis.lods.insert(
+                            (view_entity, *visible_entity),
+                            mesh_hysteresis.apply(
+                                lod,
+                                lod_material.material.transition.width,
+                                previous,
+                            ),
+                        );
+                    }
+
+                    let LodMaterialPipelines {
+                        lod_pipeline,
+                        pipelines,
+                        pipeline_cache,
+                        ..
+                    } = &mut pipelines;
                     let pipeline = pipelines
-                        .specialize(&pipeline_cache, &lod_pipeline, key, &mesh.layout)
+                        .specialize(pipeline_cache, lod_pipeline, key, &mesh.layout)
                         .unwrap();
 
                     transparent_phase.add(Transparent3d {
@@ -388,6 +795,7 @@ pub struct PreparedLodMaterial<const U: usize> {
     pub bindings: Vec<OwnedBindingResource>,
     pub bind_group: BindGroup,
     pub key: <LodMaterial<U> as AsBindGroup>::Data,
+    pub material: LodMaterial<U>,
 }
 
 #[derive(Resource)]
@@ -458,24 +866,25 @@ impl<const U: usize> Default for PrepareNextFrameLodMaterials<U> {
     }
 }
 
+/// What the bind group of a [`LodMaterial`] is prepared from.
+#[derive(SystemParam)]
+pub struct LodMaterialBindings<'w, const U: usize, M: Material> {
+    render_device: Res<'w, RenderDevice>,
+    images: Res<'w, RenderAssets<Image>>,
+    fallback_image: Res<'w, FallbackImage>,
+    pipeline: Res<'w, LodMaterialPipeline<U, M>>,
+    focus_uniform: Res<'w, LodFocusUniform>,
+}
+
 fn prepare_lod_materials<const U: usize, M: Material>(
     mut prepare_next_frame: Local<PrepareNextFrameLodMaterials<U>>,
     mut extracted_assets: ResMut<ExtractedLodMaterials<U>>,
     mut render_materials: ResMut<RenderLodMaterials<U>>,
-    render_device: Res<RenderDevice>,
-    images: Res<RenderAssets<Image>>,
-    fallback_image: Res<FallbackImage>,
-    pipeline: Res<LodMaterialPipeline<U, M>>,
+    bindings: LodMaterialBindings<U, M>,
 ) {
     let queued_assets = mem::take(&mut prepare_next_frame.assets);
     for (handle, material) in queued_assets.into_iter() {
-        match prepare_lod_material(
-            &material,
-            &render_device,
-            &images,
-            &fallback_image,
-            &pipeline,
-        ) {
+        match prepare_lod_material(&material, &bindings) {
             Ok(prepared_asset) => {
                 render_materials.insert(handle, prepared_asset);
             }
@@ -490,13 +899,7 @@ fn prepare_lod_materials<const U: usize, M: Material>(
     }
 
     for (handle, material) in mem::take(&mut extracted_assets.extracted) {
-        match prepare_lod_material(
-            &material,
-            &render_device,
-            &images,
-            &fallback_image,
-            &pipeline,
-        ) {
+        match prepare_lod_material(&material, &bindings) {
             Ok(prepared_asset) => {
                 render_materials.insert(handle, prepared_asset);
             }
@@ -509,17 +912,55 @@ fn prepare_lod_materials<const U: usize, M: Material>(
 
 fn prepare_lod_material<const U: usize, M: Material>(
     lod_material: &LodMaterial<U>,
-    render_device: &RenderDevice,
-    images: &RenderAssets<Image>,
-    fallback_image: &FallbackImage,
-    pipeline: &LodMaterialPipeline<U, M>,
+    LodMaterialBindings {
+        render_device,
+        images,
+        fallback_image,
+        pipeline,
+        focus_uniform,
+    }: &LodMaterialBindings<U, M>,
 ) -> Result<PreparedLodMaterial<U>, AsBindGroupError> {
-    let prepared =
-        lod_material.as_bind_group(&pipeline.lod_layout, render_device, images, fallback_image)?;
+    let prepared = lod_material.as_bind_group(
+        &pipeline.material_layout,
+        render_device,
+        images,
+        fallback_image,
+    )?;
+
+    let focus_binding = focus_uniform
+        .buffer
+        .binding()
+        .ok_or(AsBindGroupError::RetryNextUpdate)?;
+
+    let mut bindings = prepared.bindings;
+    bindings.push(OwnedBindingResource::Buffer(lod_array_buffer(
+        render_device,
+        &lod_material.lod_distances,
+    )));
+
+    let mut entries = bindings
+        .iter()
+        .enumerate()
+        .map(|(binding, resource)| BindGroupEntry {
+            binding: binding as u32,
+            resource: resource.get_binding(),
+        })
+        .collect::<Vec<_>>();
+    entries.push(BindGroupEntry {
+        binding: entries.len() as u32,
+        resource: focus_binding,
+    });
+
+    let bind_group = render_device.create_bind_group(&BindGroupDescriptor {
+        label: Some("lod_bind_group"),
+        layout: &pipeline.lod_layout,
+        entries: &entries,
+    });
 
     Ok(PreparedLodMaterial {
-        bindings: prepared.bindings,
-        bind_group: prepared.bind_group,
+        bindings,
+        bind_group,
         key: prepared.data,
+        material: lod_material.clone(),
     })
 }
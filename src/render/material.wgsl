@@ -3,8 +3,22 @@
 #import bevy_pbr::mesh_view_bindings      view
 #import bevy_pbr::mesh_vertex_output      MeshVertexOutput
 #import bevy_mesh_pop::lod_functions as   lod_functions
-#import bevy_mesh_pop::lod_bindings       max_lod, size, period
+#import bevy_mesh_pop::lod_bindings       max_lod, size, period, transition, pixel_tolerance, lod_distances, focus
 
+fn calculate_lod() -> f32 {
+    let world_position = mesh_functions::mesh_position_local_to_world(mesh.model, vec4<f32>(vec3<f32>(size) / 2.0, 1.0));
+
+    var focus_position = view.world_position;
+    if focus.enabled != 0u {
+        focus_position = focus.position;
+    }
+
+    let distance = length(world_position.xyz - focus_position);
+    let pixel_scale = view.projection[1][1] * view.viewport.w / 2.0;
+    let voxel_pixels = lod_functions::voxel_pixels(mesh.model, world_position, view.inverse_view, pixel_scale, view.projection[3][3] == 1.0);
+
+    return lod_functions::ease_lod(#{EASING}u, distance, voxel_pixels, period, max_lod, pixel_tolerance, lod_distances);
+}
 
 struct Vertex {
     @builtin(instance_index) instance_index: u32,
@@ -23,15 +37,20 @@ struct Vertex {
 @vertex
 fn vertex(vertex: Vertex) -> MeshVertexOutput {
 
-    let lod = lod_functions::calculate_lod();
+    var lod: f32;
+    if (vertex.instance_index & ~lod_functions::OUTGOING_INSTANCE) == 0u {
+        lod = calculate_lod();
+    } else {
+        lod = lod_functions::instance_lod(vertex.instance_index);
+    }
 
     var position: vec3<f32>;
 
-    if (lod % 1.0) <= 0.25 && lod >= 1.0 {
+    if (lod % 1.0) < transition.width && lod >= 1.0 {
         let floor_lod = u32(floor(lod));
         let ceil_lod = u32(ceil(lod));
 
-        let is_next = vertex.index < lod_functions::lod_index(floor_lod) * 6u;
+        let is_next = !lod_functions::is_outgoing(vertex.instance_index);
 
         var current_position = lod_functions::position_into_lod(vertex.index, vertex.position, vertex.normal, floor_lod);
 
@@ -40,13 +59,16 @@ fn vertex(vertex: Vertex) -> MeshVertexOutput {
         next_position = lod_functions::position_into_lod(vertex.index, vertex.position, vertex.normal, floor_lod - 1u);
 
         let face = lod_functions::get_face(vertex.normal);
+        let progress = (lod % 1.0) / transition.width;
+
         if is_next {
-            position = mix(current_position, next_position, 1.0 - (lod % 1.0) / 0.25);
+            position = mix(current_position, next_position, 1.0 - progress);
 
         } else {
-            let distance = lod_functions::mesh_distance();
-            current_position -= f32(face.n_sign) * vec3<f32>(face.n_axis) * clamp(lod % 1.0, 0.1, 0.25) * (f32(((vertex.index / 4u) % 4u) + 1u) * 2.0) / 100.0;
-            position = mix(current_position, next_position, 1.0 - (lod % 1.0) / 0.25);
+#ifdef DEPTH_OFFSET_NUDGE
+            current_position -= f32(face.n_sign) * vec3<f32>(face.n_axis) * clamp(progress, 0.4, 1.0) * f32(((vertex.index / 4u) % 4u) + 1u) / 4.0 * transition.depth_offset_scale;
+#endif
+            position = mix(current_position, next_position, 1.0 - progress);
         }
 
 
@@ -58,7 +80,7 @@ fn vertex(vertex: Vertex) -> MeshVertexOutput {
     }
 
 
-    // let lod = u32(floor(lod_functions::calculate_lod()));
+    // let lod = u32(floor(calculate_lod()));
     // let position = lod_functions::position_into_lod(vertex.index, vertex.position, vertex.normal, lod);
 
     var out: MeshVertexOutput;
@@ -66,6 +88,7 @@ fn vertex(vertex: Vertex) -> MeshVertexOutput {
     out.world_normal = mesh_functions::mesh_normal_local_to_world(vertex.normal);
     out.world_position = mesh_functions::mesh_position_local_to_world(mesh.model, vec4<f32>(vec3<f32>(position), 1.0));
     out.position = mesh_functions::mesh_position_world_to_clip(out.world_position);
+
     out.uv = vertex.uv;
 
 #ifdef VERTEX_TANGENTS
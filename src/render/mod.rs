@@ -1,7 +1,30 @@
 pub mod material;
+pub mod allocator;
+pub(crate) mod bounds;
+pub mod depth_offset;
 pub mod easing;
+pub mod focus;
+pub mod hysteresis;
+pub mod indirect;
+pub mod loader;
+pub mod stream;
 
-use bevy::{asset::load_internal_asset, prelude::*, reflect::TypeUuid};
+use bevy::{
+    asset::load_internal_asset,
+    prelude::*,
+    reflect::TypeUuid,
+    render::{
+        extract_component::ExtractComponentPlugin, extract_resource::ExtractResourcePlugin,
+        Render, RenderApp, RenderSet,
+    },
+};
+
+use self::{
+    allocator::LodMeshStorage,
+    focus::{extract_lod_focus, prepare_lod_focus, ExtractedLodFocus, LodFocusUniform},
+    hysteresis::LodHysteresis,
+    indirect::LodDrawMode,
+};
 
 pub const LOD_BINDINGS_SHADER_HANDLE: HandleUntyped =
     HandleUntyped::weak_from_u64(Shader::TYPE_UUID, 1983927262504844127);
@@ -12,6 +35,9 @@ pub const LOD_FUNCTIONS_SHADER_HANDLE: HandleUntyped =
 pub const LOD_MATERIAL_SHADER_HANDLE: HandleUntyped =
     HandleUntyped::weak_from_u64(Shader::TYPE_UUID, 5474541954525159662);
 
+pub const LOD_INDIRECT_SHADER_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(Shader::TYPE_UUID, 8310652217463409716);
+
 pub struct LodRenderPlugin;
 
 impl Plugin for LodRenderPlugin {
@@ -34,5 +60,32 @@ impl Plugin for LodRenderPlugin {
             "material.wgsl",
             Shader::from_wgsl
         );
+        load_internal_asset!(
+            app,
+            LOD_INDIRECT_SHADER_HANDLE,
+            "lod_indirect.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.init_resource::<LodDrawMode>()
+            .init_resource::<LodMeshStorage>()
+            .add_plugins((
+                ExtractComponentPlugin::<LodHysteresis>::extract_visible(),
+                ExtractResourcePlugin::<LodDrawMode>::default(),
+                ExtractResourcePlugin::<LodMeshStorage>::default(),
+            ));
+
+        if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .init_resource::<ExtractedLodFocus>()
+                .add_systems(ExtractSchedule, extract_lod_focus)
+                .add_systems(Render, prepare_lod_focus.in_set(RenderSet::Prepare));
+        }
+    }
+
+    fn finish(&self, app: &mut App) {
+        if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app.init_resource::<LodFocusUniform>();
+        }
     }
 }
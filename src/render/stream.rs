@@ -0,0 +1,110 @@
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+
+use crate::{build_pop_mesh, EncodeQuad, PopStreamDecoder, UnorientedQuad};
+
+use super::material::LodMaterial;
+
+type LodStreamQuery<'a, const U: usize, Q> = (
+    Entity,
+    &'a mut LodStream<U, Q>,
+    &'a Handle<Mesh>,
+    &'a Handle<LodMaterial<U>>,
+);
+
+/// Grows the mesh and [`LodMaterial`] of an entity as a stream written by
+/// [`PopBuffer::write_stream`](crate::PopBuffer::write_stream) arrives.
+///
+/// Bytes pushed with [`push`](Self::push) are decoded every frame. Until the stream is complete,
+/// the mesh holds the LODs loaded so far snapped to the finest of them, so closer LODs draw as
+/// that one instead of with holes. The component is removed if the stream is invalid.
+///
+/// Streams are only decoded once [`LodStreamPlugin`] has been added for `U` and `Q`.
+#[derive(Component)]
+pub struct LodStream<const U: usize, Q: EncodeQuad = UnorientedQuad> {
+    decoder: PopStreamDecoder<U, Q>,
+}
+
+impl<const U: usize, Q: EncodeQuad> LodStream<U, Q> {
+    /// Creates a stream for the quads of a chunk of `X * Y * Z` voxels.
+    pub fn new<const X: u32, const Y: u32, const Z: u32>() -> Self {
+        Self {
+            decoder: PopStreamDecoder::new::<X, Y, Z>(),
+        }
+    }
+
+    #[inline]
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.decoder.push(bytes);
+    }
+
+    #[inline]
+    pub fn loaded_lod(&self) -> Option<usize> {
+        self.decoder.loaded_lod()
+    }
+
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.decoder.is_complete()
+    }
+}
+
+/// Decodes the [`LodStream`]s of [`LodMaterial`]s with `U` LODs and quads of type `Q`.
+pub struct LodStreamPlugin<const U: usize, Q: EncodeQuad = UnorientedQuad>(PhantomData<Q>);
+
+impl<const U: usize, Q: EncodeQuad> Default for LodStreamPlugin<U, Q> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<const U: usize, Q> Plugin for LodStreamPlugin<U, Q>
+where
+    Q: EncodeQuad + Send + Sync + 'static,
+{
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, update_lod_streams::<U, Q>);
+    }
+}
+
+fn update_lod_streams<const U: usize, Q>(
+    mut commands: Commands,
+    mut streams: Query<LodStreamQuery<U, Q>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<LodMaterial<U>>>,
+) where
+    Q: EncodeQuad + Send + Sync + 'static,
+{
+    for (entity, mut stream, mesh_handle, material_handle) in &mut streams {
+        if stream.is_complete() {
+            continue;
+        }
+
+        let mut loaded_lod = None;
+        loop {
+            match stream.decoder.decode_lod() {
+                Ok(Some(lod)) => loaded_lod = Some(lod),
+                Ok(None) => break,
+                Err(error) => {
+                    warn!("invalid LOD stream for {entity:?}: {error}");
+                    commands.entity(entity).remove::<LodStream<U, Q>>();
+                    break;
+                }
+            }
+        }
+
+        let Some(lod) = loaded_lod else {
+            continue;
+        };
+
+        let buffer = stream.decoder.buffer().snap_to_lod(lod);
+
+        if let Some(material) = materials.get_mut(material_handle) {
+            material.face_counts = buffer.get_face_counts();
+        }
+        if let Some(mesh) = meshes.get_mut(mesh_handle) {
+            *mesh = build_pop_mesh(&buffer);
+        }
+    }
+}
@@ -0,0 +1,287 @@
+use bevy_math::{IVec3, Vec2, Vec3, Vec3Swizzles};
+
+use crate::{terrain::has_faces, ChunkShape, TerrainVoxel};
+
+/// A signed distance field, negative inside of the shape.
+///
+/// Distances must not be overestimated for [`SdfSampling::Conservative`] to keep thin
+/// features, which holds for exact distances and the operations of [`SdfNode`].
+pub trait Sdf {
+    fn distance(&self, position: Vec3) -> f32;
+
+    /// The distance at `position` and the material of the nearest surface.
+    #[inline]
+    fn sample(&self, position: Vec3) -> (f32, u8) {
+        (self.distance(position), 0)
+    }
+}
+
+impl<F: Fn(Vec3) -> f32> Sdf for F {
+    #[inline]
+    fn distance(&self, position: Vec3) -> f32 {
+        self(position)
+    }
+}
+
+/// A tree of primitives combined by constructive solid geometry.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SdfNode {
+    Sphere {
+        center: Vec3,
+        radius: f32,
+    },
+    Cuboid {
+        center: Vec3,
+        half_size: Vec3,
+    },
+    Capsule {
+        start: Vec3,
+        end: Vec3,
+        radius: f32,
+    },
+    /// A torus around the `y` axis.
+    Torus {
+        center: Vec3,
+        major_radius: f32,
+        minor_radius: f32,
+    },
+    /// The half space below a plane through `normal * offset`.
+    Plane {
+        normal: Vec3,
+        offset: f32,
+    },
+    Union(Box<SdfNode>, Box<SdfNode>),
+    Intersection(Box<SdfNode>, Box<SdfNode>),
+    Difference(Box<SdfNode>, Box<SdfNode>),
+    /// A union blending the shapes within `radius` of each other.
+    SmoothUnion {
+        a: Box<SdfNode>,
+        b: Box<SdfNode>,
+        radius: f32,
+    },
+    Translate {
+        offset: Vec3,
+        node: Box<SdfNode>,
+    },
+    /// Gives a material to the surfaces of `node`.
+    Material {
+        material: u8,
+        node: Box<SdfNode>,
+    },
+}
+
+impl SdfNode {
+    #[inline]
+    pub fn sphere(center: Vec3, radius: f32) -> Self {
+        Self::Sphere { center, radius }
+    }
+
+    #[inline]
+    pub fn cuboid(center: Vec3, half_size: Vec3) -> Self {
+        Self::Cuboid { center, half_size }
+    }
+
+    #[inline]
+    pub fn capsule(start: Vec3, end: Vec3, radius: f32) -> Self {
+        Self::Capsule { start, end, radius }
+    }
+
+    #[inline]
+    pub fn torus(center: Vec3, major_radius: f32, minor_radius: f32) -> Self {
+        Self::Torus {
+            center,
+            major_radius,
+            minor_radius,
+        }
+    }
+
+    #[inline]
+    pub fn plane(normal: Vec3, offset: f32) -> Self {
+        Self::Plane {
+            normal: normal.normalize(),
+            offset,
+        }
+    }
+
+    #[inline]
+    pub fn union(self, other: Self) -> Self {
+        Self::Union(Box::new(self), Box::new(other))
+    }
+
+    #[inline]
+    pub fn intersection(self, other: Self) -> Self {
+        Self::Intersection(Box::new(self), Box::new(other))
+    }
+
+    #[inline]
+    pub fn difference(self, other: Self) -> Self {
+        Self::Difference(Box::new(self), Box::new(other))
+    }
+
+    #[inline]
+    pub fn smooth_union(self, other: Self, radius: f32) -> Self {
+        Self::SmoothUnion {
+            a: Box::new(self),
+            b: Box::new(other),
+            radius,
+        }
+    }
+
+    #[inline]
+    pub fn translate(self, offset: Vec3) -> Self {
+        Self::Translate {
+            offset,
+            node: Box::new(self),
+        }
+    }
+
+    #[inline]
+    pub fn with_material(self, material: u8) -> Self {
+        Self::Material {
+            material,
+            node: Box::new(self),
+        }
+    }
+}
+
+impl Sdf for SdfNode {
+    #[inline]
+    fn distance(&self, position: Vec3) -> f32 {
+        self.sample(position).0
+    }
+
+    fn sample(&self, position: Vec3) -> (f32, u8) {
+        match self {
+            Self::Sphere { center, radius } => ((position - *center).length() - radius, 0),
+            Self::Cuboid { center, half_size } => {
+                let q = (position - *center).abs() - *half_size;
+                (q.max(Vec3::ZERO).length() + q.max_element().min(0.0), 0)
+            }
+            Self::Capsule { start, end, radius } => {
+                let (along, offset) = (*end - *start, position - *start);
+                let t =
+                    (offset.dot(along) / along.length_squared().max(f32::EPSILON)).clamp(0.0, 1.0);
+                ((offset - along * t).length() - radius, 0)
+            }
+            Self::Torus {
+                center,
+                major_radius,
+                minor_radius,
+            } => {
+                let p = position - *center;
+                let q = Vec2::new(p.xz().length() - major_radius, p.y);
+                (q.length() - minor_radius, 0)
+            }
+            Self::Plane { normal, offset } => (position.dot(*normal) - offset, 0),
+            Self::Union(a, b) => {
+                let (a, b) = (a.sample(position), b.sample(position));
+                if a.0 <= b.0 {
+                    a
+                } else {
+                    b
+                }
+            }
+            Self::Intersection(a, b) => {
+                let (a, b) = (a.sample(position), b.sample(position));
+                if a.0 >= b.0 {
+                    a
+                } else {
+                    b
+                }
+            }
+            Self::Difference(a, b) => {
+                let (a, b) = (a.sample(position), b.sample(position));
+                // The carved surface keeps the material of `a`.
+                (a.0.max(-b.0), a.1)
+            }
+            Self::SmoothUnion { a, b, radius } => {
+                let (a, b) = (a.sample(position), b.sample(position));
+                let h = (radius - (a.0 - b.0).abs()).max(0.0) / radius.max(f32::EPSILON);
+                let distance = a.0.min(b.0) - h * h * radius * 0.25;
+                (distance, if a.0 <= b.0 { a.1 } else { b.1 })
+            }
+            Self::Translate { offset, node } => node.sample(position - *offset),
+            Self::Material { material, node } => (node.distance(position), *material),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SdfSampling {
+    /// Voxels are solid where their centre is inside.
+    Center,
+    /// Voxels are solid where the surface could pass through them, so that features thinner
+    /// than a voxel are kept.
+    #[default]
+    Conservative,
+}
+
+/// Samples [`Sdf`]s into padded chunks of [`TerrainVoxel`]s, which take the material of the
+/// nearest surface.
+///
+/// The voxel at `position` spans from `position * voxel_size` to
+/// `(position + 1) * voxel_size` in the space of the field.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SdfVoxelizer {
+    pub voxel_size: f32,
+    pub sampling: SdfSampling,
+}
+
+impl Default for SdfVoxelizer {
+    fn default() -> Self {
+        Self {
+            voxel_size: 1.0,
+            sampling: SdfSampling::Conservative,
+        }
+    }
+}
+
+impl SdfVoxelizer {
+    #[inline]
+    pub fn get(&self, sdf: &impl Sdf, position: IVec3) -> TerrainVoxel {
+        let center = (position.as_vec3() + Vec3::splat(0.5)) * self.voxel_size;
+        let (distance, material) = sdf.sample(center);
+
+        let threshold = match self.sampling {
+            SdfSampling::Center => 0.0,
+            // Half of the diagonal of a voxel.
+            SdfSampling::Conservative => self.voxel_size * 3f32.sqrt() * 0.5,
+        };
+
+        if distance <= threshold {
+            TerrainVoxel::Solid(material)
+        } else {
+            TerrainVoxel::Empty
+        }
+    }
+
+    /// Fills the padded chunk of `X * Y * Z` voxels at `chunk`, as laid out by
+    /// [`ChunkShape::fill_padded`].
+    pub fn fill_chunk<const X: u32, const Y: u32, const Z: u32>(
+        &self,
+        sdf: &impl Sdf,
+        chunk: IVec3,
+        voxels: &mut [TerrainVoxel],
+    ) {
+        ChunkShape::<X, Y, Z>::fill_padded(chunk, voxels, |position| self.get(sdf, position));
+    }
+
+    /// Fills the chunks covering the field from `min` to `max`, skipping those without any
+    /// visible face.
+    pub fn chunks<'a, const X: u32, const Y: u32, const Z: u32>(
+        &'a self,
+        sdf: &'a impl Sdf,
+        min: Vec3,
+        max: Vec3,
+    ) -> impl Iterator<Item = (IVec3, Vec<TerrainVoxel>)> + 'a {
+        let min = (min / self.voxel_size).floor().as_ivec3();
+        let max = (max / self.voxel_size).floor().as_ivec3();
+
+        ChunkShape::<X, Y, Z>::chunk_range(min, max).filter_map(move |chunk| {
+            let mut voxels = vec![TerrainVoxel::Empty; (X * Y * Z) as usize];
+            self.fill_chunk::<X, Y, Z>(sdf, chunk, &mut voxels);
+
+            has_faces::<X, Y, Z>(&voxels).then_some((chunk, voxels))
+        })
+    }
+}
@@ -0,0 +1,231 @@
+use std::io::{self, Read, Write};
+
+use bevy_math::UVec3;
+
+use crate::{
+    encoding::{
+        read_header, read_quads, read_u32, write_header, write_quads, BitReader, BitWriter,
+        QuadBits, HEADER_LEN,
+    },
+    DecodeError, EncodeQuad, OrientedBlockFace, PopBuffer,
+};
+
+const MAGIC: [u8; 4] = *b"POPS";
+const COUNTS_LEN: usize = 6 * 4;
+/// The most bytes [`PopStreamReader`] reads at once, so that invalid counts can't make it
+/// allocate more than the stream holds.
+const READ_LEN: usize = 4096;
+
+impl<const M: usize, Q: EncodeQuad> PopBuffer<M, Q> {
+    /// Writes the quads meshed from a chunk of `X * Y * Z` voxels so that they can be read
+    /// progressively by a [`PopStreamReader`] or [`PopStreamDecoder`].
+    ///
+    /// After the header of [`encode`](Self::encode), each LOD is written from the coarsest, with
+    /// the number of quads of each face followed by the quads. `writer` is flushed after each
+    /// LOD, so clients can draw the coarse LODs while the rest arrives.
+    pub fn write_stream<const X: u32, const Y: u32, const Z: u32>(
+        &self,
+        mut writer: impl Write,
+    ) -> io::Result<()> {
+        let bits = QuadBits::new(UVec3::new(X, Y, Z));
+
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        write_header::<X, Y, Z, M, Q>(&mut header, MAGIC);
+        writer.write_all(&header)?;
+
+        for group in self.groups.iter().rev() {
+            let mut bytes =
+                Vec::with_capacity(COUNTS_LEN + (group.num_quads() * bits.per_quad::<Q>() + 7) / 8);
+            for quads in group.groups.iter() {
+                bytes.extend_from_slice(&(quads.len() as u32).to_le_bytes());
+            }
+
+            let mut quad_writer = BitWriter::new(bytes);
+            for quads in group.groups.iter() {
+                write_quads(&mut quad_writer, quads, &bits);
+            }
+
+            writer.write_all(&quad_writer.finish())?;
+            writer.flush()?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Stage {
+    Header,
+    Counts,
+    Quads { counts: [u32; 6], len: usize },
+    Done,
+}
+
+/// Decodes a stream written by [`PopBuffer::write_stream`] from bytes as they arrive.
+///
+/// Each LOD is decoded as soon as all of its bytes have been pushed. Until the stream is
+/// complete, [`buffer`](Self::buffer) only holds the LODs from [`loaded_lod`](Self::loaded_lod)
+/// up, which [`PopBuffer::snap_to_lod`] turns into a mesh that can already be drawn.
+pub struct PopStreamDecoder<const M: usize, Q: EncodeQuad> {
+    bits: Option<QuadBits>,
+    check_header: fn(&[u8]) -> Result<QuadBits, DecodeError>,
+    /// The number of voxel faces in the chunk, which no LOD can have more quads than.
+    max_quads: u64,
+    pending: Vec<u8>,
+    stage: Stage,
+    loaded: usize,
+    buffer: PopBuffer<M, Q>,
+}
+
+impl<const M: usize, Q: EncodeQuad> PopStreamDecoder<M, Q> {
+    /// Creates a decoder for the quads of a chunk of `X * Y * Z` voxels.
+    pub fn new<const X: u32, const Y: u32, const Z: u32>() -> Self {
+        Self {
+            bits: None,
+            check_header: |header| read_header::<X, Y, Z, M, Q>(header, MAGIC),
+            max_quads: 6 * X as u64 * Y as u64 * Z as u64,
+            pending: Vec::new(),
+            stage: Stage::Header,
+            loaded: 0,
+            buffer: PopBuffer::new(),
+        }
+    }
+
+    #[inline]
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.pending.extend_from_slice(bytes);
+    }
+
+    /// The number of bytes that must still be pushed before the next step can be decoded.
+    #[inline]
+    pub fn bytes_needed(&self) -> usize {
+        let len = match self.stage {
+            Stage::Header => HEADER_LEN,
+            Stage::Counts => COUNTS_LEN,
+            Stage::Quads { len, .. } => len,
+            Stage::Done => 0,
+        };
+        len.saturating_sub(self.pending.len())
+    }
+
+    /// Decodes the next LOD if all of its bytes have been pushed, returning that LOD.
+    pub fn decode_lod(&mut self) -> Result<Option<usize>, DecodeError> {
+        loop {
+            match self.stage {
+                Stage::Header if self.pending.len() >= HEADER_LEN => {
+                    self.bits = Some((self.check_header)(&self.pending)?);
+                    self.pending.drain(..HEADER_LEN);
+                    self.stage = if M == 0 { Stage::Done } else { Stage::Counts };
+                }
+                Stage::Counts if self.pending.len() >= COUNTS_LEN => {
+                    let bits = self.bits.as_ref().unwrap();
+                    let counts: [u32; 6] =
+                        std::array::from_fn(|i| read_u32(&self.pending[i * 4..]));
+
+                    let num_quads = counts.iter().map(|&count| count as u64).sum::<u64>();
+                    if num_quads > self.max_quads {
+                        return Err(DecodeError::TooManyQuads);
+                    }
+                    let len = (num_quads as usize * bits.per_quad::<Q>() + 7) / 8;
+
+                    self.pending.drain(..COUNTS_LEN);
+                    self.stage = Stage::Quads { counts, len };
+                }
+                Stage::Quads { counts, len } if self.pending.len() >= len => {
+                    let bits = self.bits.as_ref().unwrap();
+                    let lod = M - 1 - self.loaded;
+
+                    let mut reader = BitReader::new(&self.pending[..len]);
+                    for (face, quads) in self.buffer.groups[lod].groups.iter_mut().enumerate() {
+                        let count = counts[face] as usize;
+                        let face = &OrientedBlockFace::FACES[face];
+                        read_quads(&mut reader, face, count, bits, quads)?;
+                    }
+                    if !reader.is_done() {
+                        return Err(DecodeError::TrailingBytes);
+                    }
+
+                    self.pending.drain(..len);
+                    self.loaded += 1;
+                    self.stage = if lod == 0 { Stage::Done } else { Stage::Counts };
+
+                    return Ok(Some(lod));
+                }
+                Stage::Done if !self.pending.is_empty() => return Err(DecodeError::TrailingBytes),
+                _ => return Ok(None),
+            }
+        }
+    }
+
+    /// The finest LOD decoded so far, which can be drawn exactly.
+    #[inline]
+    pub fn loaded_lod(&self) -> Option<usize> {
+        (self.loaded > 0).then(|| M - self.loaded)
+    }
+
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        matches!(self.stage, Stage::Done)
+    }
+
+    #[inline]
+    pub fn buffer(&self) -> &PopBuffer<M, Q> {
+        &self.buffer
+    }
+
+    #[inline]
+    pub fn into_buffer(self) -> PopBuffer<M, Q> {
+        self.buffer
+    }
+}
+
+/// Reads a stream written by [`PopBuffer::write_stream`] one LOD at a time, from the coarsest.
+///
+/// Only the bytes of each LOD are read, so the stream can be followed by other data.
+pub struct PopStreamReader<R, const M: usize, Q: EncodeQuad> {
+    reader: R,
+    decoder: PopStreamDecoder<M, Q>,
+}
+
+impl<R: Read, const M: usize, Q: EncodeQuad> PopStreamReader<R, M, Q> {
+    /// Creates a reader for the quads of a chunk of `X * Y * Z` voxels.
+    pub fn new<const X: u32, const Y: u32, const Z: u32>(reader: R) -> Self {
+        Self {
+            reader,
+            decoder: PopStreamDecoder::new::<X, Y, Z>(),
+        }
+    }
+
+    /// Reads the next LOD, returning `None` once every LOD has been read.
+    pub fn read_lod(&mut self) -> io::Result<Option<usize>> {
+        let mut chunk = [0; READ_LEN];
+
+        while !self.decoder.is_complete() {
+            if let Some(lod) = self.decoder.decode_lod()? {
+                return Ok(Some(lod));
+            }
+
+            let chunk = &mut chunk[..self.decoder.bytes_needed().min(READ_LEN)];
+            self.reader.read_exact(chunk)?;
+            self.decoder.push(chunk);
+        }
+
+        Ok(None)
+    }
+
+    /// Reads every remaining LOD.
+    pub fn read_to_end(mut self) -> io::Result<PopBuffer<M, Q>> {
+        while self.read_lod()?.is_some() {}
+        Ok(self.decoder.into_buffer())
+    }
+
+    #[inline]
+    pub fn decoder(&self) -> &PopStreamDecoder<M, Q> {
+        &self.decoder
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> (R, PopStreamDecoder<M, Q>) {
+        (self.reader, self.decoder)
+    }
+}
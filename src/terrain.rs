@@ -0,0 +1,56 @@
+use bevy_math::UVec3;
+
+use crate::{ChunkShape, MergeVoxel, MeshVoxel, VoxelVisibility};
+
+/// A voxel of generated terrain or shapes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TerrainVoxel {
+    #[default]
+    Empty,
+    /// A solid voxel of a material, such as from a material map.
+    Solid(u8),
+}
+
+impl MeshVoxel for TerrainVoxel {
+    #[inline]
+    fn get_visibility(&self) -> VoxelVisibility {
+        match self {
+            Self::Empty => VoxelVisibility::Empty,
+            Self::Solid(_) => VoxelVisibility::Opaque,
+        }
+    }
+}
+
+impl MergeVoxel for TerrainVoxel {
+    type MergeValue = Self;
+    type MergeValueFacingNeighbour = Self;
+
+    #[inline]
+    fn merge_value(&self) -> Self::MergeValue {
+        *self
+    }
+
+    #[inline]
+    fn merge_value_facing_neighbour(&self) -> Self::MergeValueFacingNeighbour {
+        *self
+    }
+}
+
+/// Whether a padded chunk has any non-empty voxel inside, without being buried in opaque ones.
+pub(crate) fn has_faces<const X: u32, const Y: u32, const Z: u32>(
+    voxels: &[impl MeshVoxel],
+) -> bool {
+    let inner = ChunkShape::<X, Y, Z>::SHAPE - UVec3::splat(2);
+
+    let has_inner_voxel = (0..X * Y * Z).any(|index| {
+        let local = ChunkShape::<X, Y, Z>::delinearize(index);
+        local.cmpge(UVec3::ONE).all()
+            && local.cmple(inner).all()
+            && voxels[index as usize].get_visibility() != VoxelVisibility::Empty
+    });
+    let is_buried = voxels
+        .iter()
+        .all(|voxel| voxel.get_visibility() == VoxelVisibility::Opaque);
+
+    has_inner_voxel && !is_buried
+}
@@ -2,7 +2,7 @@ use seq_macro::seq;
 
 use crate::{
     geometry::{quad::UnorientedUnitQuad, shape::ChunkShape},
-    MeshVoxel, PopBuffer, UnorientedRegularQuad, VisitedBuffer, VoxelVisibility,
+    MeshVoxel, QuadMesher, QuadSink, UnorientedRegularQuad, VisitedBuffer, VoxelVisibility,
 };
 
 pub fn visible_faces_quads<
@@ -14,12 +14,11 @@ pub fn visible_faces_quads<
 >(
     voxels: &[V],
     visited: &mut VisitedBuffer,
-    output: &mut PopBuffer<M, UnorientedUnitQuad>,
+    output: &mut impl QuadSink<UnorientedUnitQuad>,
 ) {
     assert_eq!(voxels.len(), (X * Y * Z) as usize);
     assert_eq!(voxels.len(), visited.visited.len());
-    assert!(M <= u8::BITS as usize);
-    assert!(M <= u8::BITS as usize);
+    assert!(M <= u16::BITS as usize);
     assert!(M <= X.ilog2() as usize);
     assert!(M <= Y.ilog2() as usize);
     assert!(M <= Z.ilog2() as usize);
@@ -80,8 +79,8 @@ fn into_lod(quad: UnorientedUnitQuad, lod: usize) -> UnorientedRegularQuad {
 ///
 /// The minimum position of `quad` must be able to fit within the visited buffer.
 #[inline]
-unsafe fn find_max_lod<const X: u32, const Y: u32, const Z: u32, const M: usize>(
-    visited: &mut [u8],
+pub(crate) unsafe fn find_max_lod<const X: u32, const Y: u32, const Z: u32, const M: usize>(
+    visited: &mut [u16],
     quad: UnorientedUnitQuad,
 ) -> usize {
     let mut max_lod: usize = 0;
@@ -109,3 +108,20 @@ unsafe fn find_max_lod<const X: u32, const Y: u32, const Z: u32, const M: usize>
 
     max_lod
 }
+
+/// [`QuadMesher`] for [`visible_faces_quads`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct VisibleFacesQuads;
+
+impl<V: MeshVoxel> QuadMesher<V> for VisibleFacesQuads {
+    type Quad = UnorientedUnitQuad;
+
+    #[inline]
+    fn mesh_quads<const X: u32, const Y: u32, const Z: u32, const M: usize>(
+        voxels: &[V],
+        visited: &mut VisitedBuffer,
+        output: &mut impl QuadSink<Self::Quad>,
+    ) {
+        visible_faces_quads::<X, Y, Z, M, V>(voxels, visited, output)
+    }
+}
@@ -0,0 +1,554 @@
+use std::{collections::HashMap, fmt};
+
+use bevy_math::{IVec3, UVec3};
+
+use crate::{terrain::has_faces, ChunkShape, MergeVoxel, MeshVoxel, VoxelVisibility};
+
+/// The largest model MagicaVoxel saves along each axis.
+const MAX_MODEL_SIZE: u32 = 256;
+
+/// A voxel of a MagicaVoxel model, as its index into the palette of the scene.
+///
+/// Index zero is empty. Voxels of different colours aren't merged.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct VoxVoxel(pub u8);
+
+impl VoxVoxel {
+    pub const EMPTY: Self = Self(0);
+
+    #[inline]
+    pub fn color(self, palette: &[[u8; 4]; 256]) -> [u8; 4] {
+        palette[self.0 as usize]
+    }
+}
+
+impl MeshVoxel for VoxVoxel {
+    #[inline]
+    fn get_visibility(&self) -> VoxelVisibility {
+        if self.0 == 0 {
+            VoxelVisibility::Empty
+        } else {
+            VoxelVisibility::Opaque
+        }
+    }
+}
+
+impl MergeVoxel for VoxVoxel {
+    type MergeValue = u8;
+    type MergeValueFacingNeighbour = u8;
+
+    #[inline]
+    fn merge_value(&self) -> Self::MergeValue {
+        self.0
+    }
+
+    #[inline]
+    fn merge_value_facing_neighbour(&self) -> Self::MergeValueFacingNeighbour {
+        self.0
+    }
+}
+
+/// A dense model, indexed by `x + y * size.x + z * size.x * size.y`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VoxModel {
+    pub size: UVec3,
+    pub voxels: Vec<VoxVoxel>,
+}
+
+impl VoxModel {
+    pub fn new(size: UVec3) -> Self {
+        Self {
+            size,
+            voxels: vec![VoxVoxel::EMPTY; size.x as usize * size.y as usize * size.z as usize],
+        }
+    }
+
+    #[inline]
+    fn index(&self, position: UVec3) -> usize {
+        (position.x + self.size.x * (position.y + self.size.y * position.z)) as usize
+    }
+
+    #[inline]
+    pub fn get(&self, position: UVec3) -> VoxVoxel {
+        if position.cmplt(self.size).all() {
+            self.voxels[self.index(position)]
+        } else {
+            VoxVoxel::EMPTY
+        }
+    }
+
+    #[inline]
+    pub fn set(&mut self, position: UVec3, voxel: VoxVoxel) {
+        let index = self.index(position);
+        self.voxels[index] = voxel;
+    }
+
+    /// Converts the model from MagicaVoxel's Z-up axes to Y-up, keeping it right-handed.
+    pub fn to_y_up(&self) -> Self {
+        let mut model = Self::new(UVec3::new(self.size.x, self.size.z, self.size.y));
+
+        for z in 0..self.size.z {
+            for y in 0..self.size.y {
+                for x in 0..self.size.x {
+                    let voxel = self.get(UVec3::new(x, y, z));
+                    model.set(UVec3::new(x, z, self.size.y - 1 - y), voxel);
+                }
+            }
+        }
+
+        model
+    }
+
+    /// Splits the model into padded chunks of `X * Y * Z` voxels, as filled by
+    /// [`ChunkShape::fill_padded`], skipping those without any visible face.
+    pub fn chunks<const X: u32, const Y: u32, const Z: u32>(
+        &self,
+    ) -> impl Iterator<Item = (UVec3, Vec<VoxVoxel>)> + '_ {
+        let max = self.size.as_ivec3() - IVec3::ONE;
+
+        ChunkShape::<X, Y, Z>::chunk_range(IVec3::ZERO, max)
+            .map(|chunk| chunk.as_uvec3())
+            .filter_map(move |chunk| {
+                let mut voxels = vec![VoxVoxel::EMPTY; (X * Y * Z) as usize];
+                ChunkShape::<X, Y, Z>::fill_padded(chunk.as_ivec3(), &mut voxels, |position| {
+                    if position.cmplt(IVec3::ZERO).any() {
+                        VoxVoxel::EMPTY
+                    } else {
+                        self.get(position.as_uvec3())
+                    }
+                });
+
+                has_faces::<X, Y, Z>(&voxels).then_some((chunk, voxels))
+            })
+    }
+}
+
+/// A placement of a model in a scene.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VoxInstance {
+    pub model: usize,
+    /// The columns of the rotation, each a signed unit axis.
+    pub rotation: [IVec3; 3],
+    /// The position of the centre of the model.
+    pub translation: IVec3,
+}
+
+impl VoxInstance {
+    const IDENTITY: [IVec3; 3] = [IVec3::X, IVec3::Y, IVec3::Z];
+
+    #[inline]
+    fn rotate(rotation: &[IVec3; 3], vector: IVec3) -> IVec3 {
+        rotation[0] * vector.x + rotation[1] * vector.y + rotation[2] * vector.z
+    }
+
+    /// The voxel of the scene covered by `position` in the model, of `size`.
+    #[inline]
+    fn transform(&self, size: UVec3, position: UVec3) -> IVec3 {
+        // Rotate around the centre of the voxel, which is a half-integer for even sizes.
+        let doubled = position.as_ivec3() * 2 + IVec3::ONE - size.as_ivec3();
+        let rotated = Self::rotate(&self.rotation, doubled).as_vec3() / 2.0;
+
+        (rotated + self.translation.as_vec3()).floor().as_ivec3()
+    }
+}
+
+/// A MagicaVoxel scene, in its Z-up axes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VoxScene {
+    pub models: Vec<VoxModel>,
+    /// Where the models are placed, from the scene graph. Files without one place every model
+    /// at the origin.
+    pub instances: Vec<VoxInstance>,
+    /// The colour of each [`VoxVoxel`] index, as RGBA.
+    pub palette: [[u8; 4]; 256],
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VoxError {
+    InvalidMagic,
+    UnexpectedEnd,
+    /// A chunk isn't where the format requires, such as `XYZI` without a preceding `SIZE`.
+    UnexpectedChunk([u8; 4]),
+    /// A model is larger than the 256 voxels per axis MagicaVoxel allows.
+    InvalidSize(UVec3),
+    /// A voxel lies outside of its model.
+    InvalidVoxel,
+    /// The scene graph refers to a missing node or model, or has a cycle.
+    InvalidScene,
+}
+
+impl fmt::Display for VoxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidMagic => write!(f, "not a .vox file"),
+            Self::UnexpectedEnd => write!(f, "unexpected end of data"),
+            Self::UnexpectedChunk(id) => {
+                write!(f, "unexpected chunk {}", String::from_utf8_lossy(id))
+            }
+            Self::InvalidSize(size) => write!(f, "model size {size} is too large"),
+            Self::InvalidVoxel => write!(f, "voxel outside of its model"),
+            Self::InvalidScene => write!(f, "invalid scene graph"),
+        }
+    }
+}
+
+impl std::error::Error for VoxError {}
+
+enum SceneNode {
+    Transform {
+        child: i32,
+        rotation: [IVec3; 3],
+        translation: IVec3,
+    },
+    Group {
+        children: Vec<i32>,
+    },
+    Shape {
+        models: Vec<i32>,
+    },
+}
+
+impl VoxScene {
+    /// Parses the `SIZE`, `XYZI` and `RGBA` chunks and the scene graph of a `.vox` file.
+    ///
+    /// Materials, layers and cameras are ignored.
+    pub fn parse(bytes: &[u8]) -> Result<Self, VoxError> {
+        let mut reader = Reader(bytes);
+
+        if reader.take(4)? != b"VOX " {
+            return Err(VoxError::InvalidMagic);
+        }
+        let _version = reader.u32()?;
+
+        let (id, _, main) = reader.chunk()?;
+        if &id != b"MAIN" {
+            return Err(VoxError::UnexpectedChunk(id));
+        }
+
+        let mut models = Vec::new();
+        let mut size = None;
+        let mut palette = default_palette();
+        let mut nodes = HashMap::new();
+
+        let mut children = Reader(main);
+        while !children.0.is_empty() {
+            let (id, content, _) = children.chunk()?;
+            let mut content = Reader(content);
+
+            match &id {
+                b"SIZE" => {
+                    let model_size = UVec3::new(content.u32()?, content.u32()?, content.u32()?);
+                    if model_size.cmpgt(UVec3::splat(MAX_MODEL_SIZE)).any() {
+                        return Err(VoxError::InvalidSize(model_size));
+                    }
+                    size = Some(model_size);
+                }
+                b"XYZI" => {
+                    let mut model =
+                        VoxModel::new(size.take().ok_or(VoxError::UnexpectedChunk(id))?);
+
+                    for _ in 0..content.u32()? {
+                        let voxel = content.take(4)?;
+                        let position =
+                            UVec3::new(voxel[0] as u32, voxel[1] as u32, voxel[2] as u32);
+                        if !position.cmplt(model.size).all() {
+                            return Err(VoxError::InvalidVoxel);
+                        }
+                        model.set(position, VoxVoxel(voxel[3]));
+                    }
+
+                    models.push(model);
+                }
+                b"RGBA" => {
+                    // Entry `i` is the colour of index `i + 1`.
+                    for color in palette.iter_mut().skip(1) {
+                        let rgba = content.take(4)?;
+                        *color = [rgba[0], rgba[1], rgba[2], rgba[3]];
+                    }
+                }
+                b"nTRN" => {
+                    let id = content.i32()?;
+                    content.dict()?;
+                    let child = content.i32()?;
+                    let _reserved = content.i32()?;
+                    let _layer = content.i32()?;
+
+                    let mut rotation = VoxInstance::IDENTITY;
+                    let mut translation = IVec3::ZERO;
+                    // Only the first frame of animated transforms is used.
+                    if content.u32()? > 0 {
+                        for (key, value) in content.dict()? {
+                            match key.as_str() {
+                                "_r" => rotation = parse_rotation(&value)?,
+                                "_t" => translation = parse_translation(&value)?,
+                                _ => {}
+                            }
+                        }
+                    }
+
+                    nodes.insert(
+                        id,
+                        SceneNode::Transform {
+                            child,
+                            rotation,
+                            translation,
+                        },
+                    );
+                }
+                b"nGRP" => {
+                    let id = content.i32()?;
+                    content.dict()?;
+                    let children = (0..content.u32()?)
+                        .map(|_| content.i32())
+                        .collect::<Result<_, _>>()?;
+
+                    nodes.insert(id, SceneNode::Group { children });
+                }
+                b"nSHP" => {
+                    let id = content.i32()?;
+                    content.dict()?;
+                    let mut shape_models = Vec::new();
+                    for _ in 0..content.u32()? {
+                        shape_models.push(content.i32()?);
+                        content.dict()?;
+                    }
+
+                    nodes.insert(
+                        id,
+                        SceneNode::Shape {
+                            models: shape_models,
+                        },
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        let mut instances = Vec::new();
+        if nodes.is_empty() {
+            instances.extend((0..models.len()).map(|model| VoxInstance {
+                model,
+                rotation: VoxInstance::IDENTITY,
+                translation: IVec3::ZERO,
+            }));
+        } else {
+            let identity = (VoxInstance::IDENTITY, IVec3::ZERO);
+            collect_instances(&nodes, 0, identity, 0, &mut instances)?;
+
+            if instances
+                .iter()
+                .any(|instance| instance.model >= models.len())
+            {
+                return Err(VoxError::InvalidScene);
+            }
+        }
+
+        Ok(Self {
+            models,
+            instances,
+            palette,
+        })
+    }
+
+    /// Places every instance of the scene into a single model covering all of them.
+    ///
+    /// Later instances overwrite earlier ones where they overlap.
+    pub fn merge(&self) -> VoxModel {
+        let mut voxels = Vec::new();
+        let mut minimum = IVec3::MAX;
+        let mut maximum = IVec3::MIN;
+
+        for instance in &self.instances {
+            let model = &self.models[instance.model];
+
+            for z in 0..model.size.z {
+                for y in 0..model.size.y {
+                    for x in 0..model.size.x {
+                        let position = UVec3::new(x, y, z);
+                        let voxel = model.get(position);
+                        if voxel == VoxVoxel::EMPTY {
+                            continue;
+                        }
+
+                        let position = instance.transform(model.size, position);
+                        minimum = minimum.min(position);
+                        maximum = maximum.max(position);
+                        voxels.push((position, voxel));
+                    }
+                }
+            }
+        }
+
+        if voxels.is_empty() {
+            return VoxModel::new(UVec3::ZERO);
+        }
+
+        let mut model = VoxModel::new((maximum - minimum + IVec3::ONE).as_uvec3());
+        for (position, voxel) in voxels {
+            model.set((position - minimum).as_uvec3(), voxel);
+        }
+
+        model
+    }
+}
+
+fn collect_instances(
+    nodes: &HashMap<i32, SceneNode>,
+    id: i32,
+    (rotation, translation): ([IVec3; 3], IVec3),
+    depth: usize,
+    instances: &mut Vec<VoxInstance>,
+) -> Result<(), VoxError> {
+    if depth > nodes.len() {
+        return Err(VoxError::InvalidScene);
+    }
+
+    match nodes.get(&id).ok_or(VoxError::InvalidScene)? {
+        SceneNode::Transform {
+            child,
+            rotation: child_rotation,
+            translation: child_translation,
+        } => {
+            let transform = (
+                child_rotation.map(|column| VoxInstance::rotate(&rotation, column)),
+                VoxInstance::rotate(&rotation, *child_translation) + translation,
+            );
+            collect_instances(nodes, *child, transform, depth + 1, instances)?;
+        }
+        SceneNode::Group { children } => {
+            for child in children {
+                collect_instances(nodes, *child, (rotation, translation), depth + 1, instances)?;
+            }
+        }
+        SceneNode::Shape { models } => {
+            for &model in models {
+                instances.push(VoxInstance {
+                    model: usize::try_from(model).map_err(|_| VoxError::InvalidScene)?,
+                    rotation,
+                    translation,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Decodes a rotation packed into a byte, as the rows of the matrix with their signs.
+fn parse_rotation(value: &str) -> Result<[IVec3; 3], VoxError> {
+    let bits: u8 = value.parse().map_err(|_| VoxError::InvalidScene)?;
+
+    let first = (bits & 3) as usize;
+    let second = ((bits >> 2) & 3) as usize;
+    if first > 2 || second > 2 || first == second {
+        return Err(VoxError::InvalidScene);
+    }
+    let third = 3 - first - second;
+
+    let mut rows = [IVec3::ZERO; 3];
+    for (row, (column, sign_bit)) in [(first, 4), (second, 5), (third, 6)]
+        .into_iter()
+        .enumerate()
+    {
+        rows[row][column] = if bits & (1 << sign_bit) == 0 { 1 } else { -1 };
+    }
+
+    // Transpose the rows into columns.
+    Ok([0, 1, 2].map(|column| IVec3::new(rows[0][column], rows[1][column], rows[2][column])))
+}
+
+fn parse_translation(value: &str) -> Result<IVec3, VoxError> {
+    let mut components = value
+        .split_whitespace()
+        .map(|component| component.parse::<i32>());
+    let mut next = || {
+        components
+            .next()
+            .and_then(Result::ok)
+            .ok_or(VoxError::InvalidScene)
+    };
+
+    Ok(IVec3::new(next()?, next()?, next()?))
+}
+
+/// The palette MagicaVoxel uses for files without an `RGBA` chunk.
+fn default_palette() -> [[u8; 4]; 256] {
+    let mut palette = [[0; 4]; 256];
+
+    // A 6 * 6 * 6 colour cube, from white to dark blue, without black.
+    let steps = [0xff, 0xcc, 0x99, 0x66, 0x33, 0x00];
+    let cube = steps.into_iter().flat_map(|r| {
+        steps
+            .into_iter()
+            .flat_map(move |g| steps.into_iter().map(move |b| [r, g, b, 0xff]))
+    });
+    for (color, rgba) in palette[1..216].iter_mut().zip(cube) {
+        *color = rgba;
+    }
+
+    // Ramps of blue, green, red and grey, skipping the values of the cube.
+    let ramp = [0xee, 0xdd, 0xbb, 0xaa, 0x88, 0x77, 0x55, 0x44, 0x22, 0x11];
+    let ramps = [[0, 0, 1], [0, 1, 0], [1, 0, 0], [1, 1, 1]]
+        .into_iter()
+        .flat_map(|mask| {
+            ramp.into_iter()
+                .map(move |value| [mask[0] * value, mask[1] * value, mask[2] * value, 0xff])
+        });
+    for (color, rgba) in palette[216..].iter_mut().zip(ramps) {
+        *color = rgba;
+    }
+
+    palette
+}
+
+/// The id, content and children of a chunk.
+type Chunk<'a> = ([u8; 4], &'a [u8], &'a [u8]);
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    #[inline]
+    fn take(&mut self, len: usize) -> Result<&'a [u8], VoxError> {
+        if self.0.len() < len {
+            return Err(VoxError::UnexpectedEnd);
+        }
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    #[inline]
+    fn u32(&mut self) -> Result<u32, VoxError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    #[inline]
+    fn i32(&mut self) -> Result<i32, VoxError> {
+        self.u32().map(|value| value as i32)
+    }
+
+    fn string(&mut self) -> Result<String, VoxError> {
+        let len = self.u32()? as usize;
+        Ok(String::from_utf8_lossy(self.take(len)?).into_owned())
+    }
+
+    fn dict(&mut self) -> Result<Vec<(String, String)>, VoxError> {
+        (0..self.u32()?)
+            .map(|_| Ok((self.string()?, self.string()?)))
+            .collect()
+    }
+
+    /// Reads a chunk, returning its id, content and children.
+    fn chunk(&mut self) -> Result<Chunk<'a>, VoxError> {
+        let id = self.take(4)?;
+        let content_len = self.u32()? as usize;
+        let children_len = self.u32()? as usize;
+
+        Ok((
+            [id[0], id[1], id[2], id[3]],
+            self.take(content_len)?,
+            self.take(children_len)?,
+        ))
+    }
+}
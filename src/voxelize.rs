@@ -0,0 +1,385 @@
+use std::fmt;
+
+use bevy::render::{
+    mesh::{Indices, Mesh, VertexAttributeValues},
+    render_resource::PrimitiveTopology,
+};
+use bevy_math::{I64Vec3, IVec3, UVec3, Vec3};
+
+use crate::{terrain::has_faces, ChunkShape, TerrainVoxel};
+
+/// The most voxels a mesh is voxelized into, taking 2 GiB of [`TerrainVoxel`]s.
+const MAX_VOXELS: usize = 1 << 30;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TriangleMeshError {
+    /// Only triangle lists can be voxelized.
+    UnsupportedTopology,
+    MissingPositions,
+    /// An index refers to a missing vertex.
+    InvalidIndex,
+    /// A line of an OBJ file, counted from one, can't be parsed.
+    InvalidObj {
+        line: usize,
+    },
+    /// A position is infinite or NaN.
+    NonFinitePosition,
+    /// At the voxel size, the mesh spans more than 2^30 voxels, or reaches more than 2^30 voxels
+    /// from the origin.
+    TooLarge,
+}
+
+impl fmt::Display for TriangleMeshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedTopology => write!(f, "mesh isn't a triangle list"),
+            Self::MissingPositions => write!(f, "mesh has no positions"),
+            Self::InvalidIndex => write!(f, "index refers to a missing vertex"),
+            Self::InvalidObj { line } => write!(f, "invalid OBJ on line {line}"),
+            Self::NonFinitePosition => write!(f, "mesh has a non-finite position"),
+            Self::TooLarge => write!(f, "mesh spans too many voxels"),
+        }
+    }
+}
+
+impl std::error::Error for TriangleMeshError {}
+
+/// Indexed triangles to voxelize.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TriangleMesh {
+    pub positions: Vec<Vec3>,
+    /// Three indices into `positions` for each triangle.
+    pub indices: Vec<u32>,
+}
+
+impl TriangleMesh {
+    pub fn from_mesh(mesh: &Mesh) -> Result<Self, TriangleMeshError> {
+        if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+            return Err(TriangleMeshError::UnsupportedTopology);
+        }
+
+        let positions: Vec<Vec3> = match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+            Some(VertexAttributeValues::Float32x3(positions)) => {
+                positions.iter().map(|&position| position.into()).collect()
+            }
+            _ => return Err(TriangleMeshError::MissingPositions),
+        };
+
+        let indices = match mesh.indices() {
+            Some(Indices::U16(indices)) => indices.iter().map(|&index| index as u32).collect(),
+            Some(Indices::U32(indices)) => indices.clone(),
+            None => (0..positions.len() as u32).collect(),
+        };
+
+        let mesh = Self { positions, indices };
+        mesh.validate()?;
+        Ok(mesh)
+    }
+
+    /// Parses the vertices and faces of a Wavefront OBJ, triangulating polygons as fans.
+    pub fn parse_obj(obj: &str) -> Result<Self, TriangleMeshError> {
+        let mut mesh = Self::default();
+
+        for (line_index, line) in obj.lines().enumerate() {
+            let error = TriangleMeshError::InvalidObj {
+                line: line_index + 1,
+            };
+            let mut words = line.split_whitespace();
+
+            match words.next() {
+                Some("v") => {
+                    let mut coordinates = words.map(|word| word.parse::<f32>());
+                    let mut next = || coordinates.next().and_then(Result::ok);
+                    let (x, y, z) = (next(), next(), next());
+                    mesh.positions.push(Vec3::new(
+                        x.ok_or(error.clone())?,
+                        y.ok_or(error.clone())?,
+                        z.ok_or(error)?,
+                    ));
+                }
+                Some("f") => {
+                    let vertices = words
+                        .map(|word| {
+                            // Texture coordinates and normals are ignored.
+                            let index: i64 = word.split('/').next()?.parse().ok()?;
+                            let len = mesh.positions.len() as i64;
+                            match index {
+                                1.. => Some(index - 1),
+                                ..=-1 => Some(len + index),
+                                0 => None,
+                            }
+                            .filter(|index| (0..len).contains(index))
+                            .map(|index| index as u32)
+                        })
+                        .collect::<Option<Vec<u32>>>()
+                        .filter(|vertices| vertices.len() >= 3)
+                        .ok_or(error)?;
+
+                    for i in 1..vertices.len() - 1 {
+                        mesh.indices.extend_from_slice(&[
+                            vertices[0],
+                            vertices[i],
+                            vertices[i + 1],
+                        ]);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(mesh)
+    }
+
+    fn validate(&self) -> Result<(), TriangleMeshError> {
+        let len = self.positions.len() as u32;
+        if self.indices.len() % 3 != 0 || self.indices.iter().any(|&index| index >= len) {
+            return Err(TriangleMeshError::InvalidIndex);
+        }
+        if !self.positions.iter().all(|position| position.is_finite()) {
+            return Err(TriangleMeshError::NonFinitePosition);
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn triangles(&self) -> impl Iterator<Item = [Vec3; 3]> + '_ {
+        self.indices
+            .chunks_exact(3)
+            .map(|triangle| [0, 1, 2].map(|i| self.positions[triangle[i] as usize]))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MeshVoxelization {
+    /// Only voxels touched by a triangle are solid.
+    #[default]
+    Surface,
+    /// Voxels enclosed by the surface are solid too. The mesh should be closed, or the
+    /// outside leaks in through its holes.
+    Solid,
+}
+
+/// Voxelizes [`TriangleMesh`]es into [`TerrainVoxel`]s of `material`.
+///
+/// As with [`SdfVoxelizer`](crate::SdfVoxelizer), the voxel at `position` spans from
+/// `position * voxel_size` to `(position + 1) * voxel_size` in the space of the mesh.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MeshVoxelizer {
+    pub voxel_size: f32,
+    pub mode: MeshVoxelization,
+    pub material: u8,
+}
+
+impl Default for MeshVoxelizer {
+    fn default() -> Self {
+        Self {
+            voxel_size: 1.0,
+            mode: MeshVoxelization::Surface,
+            material: 0,
+        }
+    }
+}
+
+impl MeshVoxelizer {
+    pub fn voxelize(&self, mesh: &TriangleMesh) -> Result<VoxelizedMesh, TriangleMeshError> {
+        mesh.validate()?;
+
+        let Some((min, max)) = mesh.positions.iter().fold(None, |bounds, &position| {
+            let (min, max) = bounds.unwrap_or((position, position));
+            Some((min.min(position), max.max(position)))
+        }) else {
+            return Ok(VoxelizedMesh {
+                minimum: IVec3::ZERO,
+                size: UVec3::ZERO,
+                voxels: Vec::new(),
+            });
+        };
+
+        // The grid has a layer of empty voxels around the mesh, from which the outside is
+        // flooded for solid voxelization.
+        let minimum = (min / self.voxel_size).floor().as_i64vec3() - I64Vec3::ONE;
+        let end = (max / self.voxel_size).floor().as_i64vec3() + I64Vec3::splat(2);
+        let Some((minimum, size, len)) = grid_size(minimum, end) else {
+            return Err(TriangleMeshError::TooLarge);
+        };
+        let mut grid = VoxelizedMesh {
+            minimum,
+            size,
+            voxels: vec![TerrainVoxel::Empty; len],
+        };
+
+        let solid = TerrainVoxel::Solid(self.material);
+        // Slightly larger than a voxel, so that triangles on the boundaries of voxels aren't
+        // missed to rounding.
+        let half_size = Vec3::splat(self.voxel_size * 0.5 * (1.0 + 1e-4));
+
+        for triangle in mesh.triangles() {
+            let first = self.voxel(triangle[0].min(triangle[1]).min(triangle[2]));
+            let last = self.voxel(triangle[0].max(triangle[1]).max(triangle[2]));
+
+            for z in first.z..=last.z {
+                for y in first.y..=last.y {
+                    for x in first.x..=last.x {
+                        let position = IVec3::new(x, y, z);
+                        let center = (position.as_vec3() + Vec3::splat(0.5)) * self.voxel_size;
+
+                        if triangle_overlaps_box(triangle, center, half_size) {
+                            let index = grid.index(position).unwrap();
+                            grid.voxels[index] = solid;
+                        }
+                    }
+                }
+            }
+        }
+
+        if self.mode == MeshVoxelization::Solid {
+            grid.fill_inside(solid);
+        }
+
+        Ok(grid)
+    }
+
+    #[inline]
+    fn voxel(&self, position: Vec3) -> IVec3 {
+        (position / self.voxel_size).floor().as_ivec3()
+    }
+}
+
+/// The minimum, size and number of voxels of the grid from `minimum` up to `end`, if it's
+/// within half the range of `i32`, so that padded chunks around it are too, and there are at
+/// most `MAX_VOXELS` voxels.
+fn grid_size(minimum: I64Vec3, end: I64Vec3) -> Option<(IVec3, UVec3, usize)> {
+    let range = i32::MIN as i64 / 2..=i32::MAX as i64 / 2;
+    let corners = [minimum.to_array(), end.to_array()];
+    if !corners.iter().flatten().all(|axis| range.contains(axis)) {
+        return None;
+    }
+
+    let size = (end - minimum).to_array().map(u32::try_from);
+    let size = UVec3::new(size[0].ok()?, size[1].ok()?, size[2].ok()?);
+    let len = (size.x as usize)
+        .checked_mul(size.y as usize)?
+        .checked_mul(size.z as usize)?;
+
+    (len <= MAX_VOXELS).then_some((minimum.as_ivec3(), size, len))
+}
+
+/// The voxels of a [`TriangleMesh`], over the box of `size` from `minimum`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VoxelizedMesh {
+    pub minimum: IVec3,
+    pub size: UVec3,
+    /// Indexed by `x + y * size.x + z * size.x * size.y` from `minimum`.
+    pub voxels: Vec<TerrainVoxel>,
+}
+
+impl VoxelizedMesh {
+    #[inline]
+    fn index(&self, position: IVec3) -> Option<usize> {
+        let local = position - self.minimum;
+        if local.cmplt(IVec3::ZERO).any() || local.as_uvec3().cmpge(self.size).any() {
+            return None;
+        }
+
+        let (local, size) = (local.as_uvec3(), self.size);
+        Some(
+            local.x as usize
+                + size.x as usize * (local.y as usize + size.y as usize * local.z as usize),
+        )
+    }
+
+    #[inline]
+    pub fn get(&self, position: IVec3) -> TerrainVoxel {
+        self.index(position)
+            .map_or(TerrainVoxel::Empty, |index| self.voxels[index])
+    }
+
+    /// Fills every empty voxel that can't be reached from the border of the grid.
+    fn fill_inside(&mut self, solid: TerrainVoxel) {
+        let mut outside = vec![false; self.voxels.len()];
+        // The minimum is on the border, so it's always empty.
+        let mut stack = vec![self.minimum];
+        outside[0] = true;
+
+        while let Some(position) = stack.pop() {
+            for offset in [
+                IVec3::X,
+                IVec3::NEG_X,
+                IVec3::Y,
+                IVec3::NEG_Y,
+                IVec3::Z,
+                IVec3::NEG_Z,
+            ] {
+                let neighbour = position + offset;
+                if let Some(index) = self.index(neighbour) {
+                    if !outside[index] && self.voxels[index] == TerrainVoxel::Empty {
+                        outside[index] = true;
+                        stack.push(neighbour);
+                    }
+                }
+            }
+        }
+
+        for (voxel, outside) in self.voxels.iter_mut().zip(outside) {
+            if !outside {
+                *voxel = solid;
+            }
+        }
+    }
+
+    /// Fills the padded chunk of `X * Y * Z` voxels at `chunk`, as laid out by
+    /// [`ChunkShape::fill_padded`].
+    pub fn fill_chunk<const X: u32, const Y: u32, const Z: u32>(
+        &self,
+        chunk: IVec3,
+        voxels: &mut [TerrainVoxel],
+    ) {
+        ChunkShape::<X, Y, Z>::fill_padded(chunk, voxels, |position| self.get(position));
+    }
+
+    /// Splits the voxels into padded chunks of `X * Y * Z` voxels, skipping those without any
+    /// visible face.
+    pub fn chunks<const X: u32, const Y: u32, const Z: u32>(
+        &self,
+    ) -> impl Iterator<Item = (IVec3, Vec<TerrainVoxel>)> + '_ {
+        let max = self.minimum + self.size.as_ivec3() - IVec3::ONE;
+
+        ChunkShape::<X, Y, Z>::chunk_range(self.minimum, max).filter_map(move |chunk| {
+            let mut voxels = vec![TerrainVoxel::Empty; (X * Y * Z) as usize];
+            self.fill_chunk::<X, Y, Z>(chunk, &mut voxels);
+
+            has_faces::<X, Y, Z>(&voxels).then_some((chunk, voxels))
+        })
+    }
+}
+
+/// Tests a triangle against a box by the separating axis theorem.
+fn triangle_overlaps_box(triangle: [Vec3; 3], center: Vec3, half_size: Vec3) -> bool {
+    let v = triangle.map(|vertex| vertex - center);
+    let edges = [v[1] - v[0], v[2] - v[1], v[0] - v[2]];
+
+    let is_separating = |axis: Vec3| {
+        let projections = v.map(|vertex| vertex.dot(axis));
+        let radius = half_size.dot(axis.abs());
+        let min = projections[0].min(projections[1]).min(projections[2]);
+        let max = projections[0].max(projections[1]).max(projections[2]);
+        min > radius || max < -radius
+    };
+
+    // The normals of the box.
+    if [Vec3::X, Vec3::Y, Vec3::Z].into_iter().any(is_separating) {
+        return false;
+    }
+
+    // The normal of the triangle.
+    if is_separating(edges[0].cross(edges[1])) {
+        return false;
+    }
+
+    // The edges of the triangle crossed with those of the box.
+    !edges.iter().any(|edge| {
+        [Vec3::X, Vec3::Y, Vec3::Z]
+            .into_iter()
+            .any(|axis| is_separating(axis.cross(*edge)))
+    })
+}
@@ -0,0 +1,49 @@
+#![allow(dead_code)]
+
+use bevy_math::Vec3;
+use block_mesh_pop::{ChunkShape, MergeVoxel, MeshVoxel, VoxelVisibility};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Voxel {
+    Empty,
+    Full,
+}
+
+impl MeshVoxel for Voxel {
+    fn get_visibility(&self) -> VoxelVisibility {
+        match self {
+            Self::Empty => VoxelVisibility::Empty,
+            Self::Full => VoxelVisibility::Opaque,
+        }
+    }
+}
+
+impl MergeVoxel for Voxel {
+    type MergeValue = Self;
+    type MergeValueFacingNeighbour = Self;
+
+    fn merge_value(&self) -> Self::MergeValue {
+        *self
+    }
+
+    fn merge_value_facing_neighbour(&self) -> Self::MergeValueFacingNeighbour {
+        *self
+    }
+}
+
+/// A chunk of 18 voxels per axis, full where `is_full` holds for the position of the voxel.
+pub fn chunk(is_full: impl Fn(Vec3) -> bool) -> Vec<Voxel> {
+    (0..18 * 18 * 18)
+        .map(|i| {
+            if is_full(ChunkShape::<18, 18, 18>::delinearize(i).as_vec3()) {
+                Voxel::Full
+            } else {
+                Voxel::Empty
+            }
+        })
+        .collect()
+}
+
+pub fn sphere() -> Vec<Voxel> {
+    chunk(|position| position.distance_squared(Vec3::splat(9.0)) < 7.5 * 7.5)
+}
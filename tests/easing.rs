@@ -0,0 +1,19 @@
+use block_mesh_pop::LodEasing;
+
+const U: u32 = 4;
+const LOD_DISTANCES: [f32; U as usize] = [0.0, 10.0, 20.0, 40.0];
+
+fn lod(easing: LodEasing, distance: f32) -> f32 {
+    easing.calculate(distance, 1.0, 100, U, 1.0, &LOD_DISTANCES)
+}
+
+#[test]
+fn piecewise_tops_out_one_lod_short() {
+    assert_eq!(lod(LodEasing::Piecewise, 5.0), 0.5);
+    assert_eq!(lod(LodEasing::Piecewise, 30.0), 2.5);
+    assert_eq!(lod(LodEasing::Piecewise, 1000.0), (U - 1) as f32);
+
+    // The other easings reach `max_lod` itself.
+    assert_eq!(lod(LodEasing::Linear, 1000.0), U as f32);
+    assert_eq!(lod(LodEasing::Sine, 1000.0), U as f32);
+}
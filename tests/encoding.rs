@@ -0,0 +1,166 @@
+mod common;
+
+use bevy_math::UVec3;
+use block_mesh_pop::{
+    Compression, DecodeError, Mesher, PopBuffer, UnorientedQuad, UnorientedUnitQuad,
+};
+
+use common::sphere;
+
+#[test]
+fn round_trip_unit_quads() {
+    let voxels = sphere();
+    let mut mesher = Mesher::<18, 18, 18, 4>::new();
+    let buffer = mesher.mesh_visible_faces(&voxels);
+    assert!(buffer.num_quads() > 0);
+
+    let bytes = buffer.encode::<18, 18, 18>();
+    let decoded = PopBuffer::<4, UnorientedUnitQuad>::decode::<18, 18, 18>(&bytes).unwrap();
+
+    assert_eq!(&decoded, buffer);
+}
+
+#[test]
+fn round_trip_quads() {
+    let voxels = sphere();
+    let mut mesher = Mesher::<18, 18, 18, 4>::new();
+    let buffer = mesher.mesh_greedy(&voxels);
+    assert!(buffer.num_quads() > 0);
+
+    let bytes = buffer.encode::<18, 18, 18>();
+    let decoded = PopBuffer::<4, UnorientedQuad>::decode::<18, 18, 18>(&bytes).unwrap();
+
+    assert_eq!(&decoded, buffer);
+}
+
+#[test]
+fn round_trip_compressed() {
+    let voxels = sphere();
+    let mut mesher = Mesher::<18, 18, 18, 4>::new();
+
+    let buffer = mesher.mesh_visible_faces(&voxels);
+    let bytes = buffer.encode_compressed::<18, 18, 18>(Compression::Delta);
+    assert!(bytes.len() < buffer.encode::<18, 18, 18>().len());
+    let decoded =
+        PopBuffer::<4, UnorientedUnitQuad>::decode_compressed::<18, 18, 18>(&bytes).unwrap();
+    assert_eq!(&decoded, buffer);
+
+    let buffer = mesher.mesh_greedy(&voxels);
+    let bytes = buffer.encode_compressed::<18, 18, 18>(Compression::default());
+    let decoded = PopBuffer::<4, UnorientedQuad>::decode_compressed::<18, 18, 18>(&bytes).unwrap();
+    assert_eq!(&decoded, buffer);
+}
+
+#[test]
+fn rejects_quads_outside_the_chunk() {
+    let voxels = sphere();
+    let mut mesher = Mesher::<18, 18, 18, 4>::new();
+    let mut bytes = mesher.mesh_greedy(&voxels).encode::<18, 18, 18>();
+
+    // The first quad follows the header and the counts of each face of each LOD, starting with
+    // its 5-bit X position, which can hold positions past the 18 voxels of the chunk.
+    bytes[19 + 4 * 6 * 4] = 0xff;
+    assert_eq!(
+        PopBuffer::<4, UnorientedQuad>::decode::<18, 18, 18>(&bytes),
+        Err(DecodeError::InvalidQuad)
+    );
+}
+
+#[cfg(feature = "lz4")]
+#[test]
+fn round_trip_lz4() {
+    let voxels = sphere();
+    let mut mesher = Mesher::<18, 18, 18, 4>::new();
+    let buffer = mesher.mesh_greedy(&voxels);
+
+    let bytes = buffer.encode_compressed::<18, 18, 18>(Compression::Lz4);
+    let decoded = PopBuffer::<4, UnorientedQuad>::decode_compressed::<18, 18, 18>(&bytes).unwrap();
+
+    assert_eq!(&decoded, buffer);
+}
+
+#[cfg(feature = "lz4")]
+#[test]
+fn lz4_rejects_oversized_payloads() {
+    let buffer = PopBuffer::<4, UnorientedQuad>::new();
+    let mut bytes = buffer.encode_compressed::<18, 18, 18>(Compression::Lz4);
+
+    // The decompressed size is prepended to the LZ4 block, after the header and compression.
+    let header_len = 4 + 2 + 3 * 4 + 1 + 1;
+    bytes[header_len..header_len + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+
+    assert_eq!(
+        PopBuffer::<4, UnorientedQuad>::decode_compressed::<18, 18, 18>(&bytes),
+        Err(DecodeError::InvalidCompressedData)
+    );
+}
+
+#[test]
+fn compressed_rejects_long_varints() {
+    let buffer = PopBuffer::<1, UnorientedQuad>::new();
+    let mut bytes = buffer.encode_compressed::<18, 18, 18>(Compression::Delta);
+
+    let header_len = 4 + 2 + 3 * 4 + 1 + 1;
+    bytes.truncate(header_len);
+    bytes.extend_from_slice(&[0xff; 10]);
+
+    assert_eq!(
+        PopBuffer::<1, UnorientedQuad>::decode_compressed::<18, 18, 18>(&bytes),
+        Err(DecodeError::VarintOverflow)
+    );
+
+    // A tenth byte with more than the 64th bit set ends the varint, but doesn't fit either.
+    bytes.truncate(header_len);
+    bytes.extend_from_slice(&[0xff; 9]);
+    bytes.push(0x02);
+
+    assert_eq!(
+        PopBuffer::<1, UnorientedQuad>::decode_compressed::<18, 18, 18>(&bytes),
+        Err(DecodeError::VarintOverflow)
+    );
+}
+
+#[test]
+fn round_trip_empty() {
+    let buffer = PopBuffer::<1, UnorientedQuad>::new();
+
+    let bytes = buffer.encode::<66, 66, 66>();
+    let decoded = PopBuffer::<1, UnorientedQuad>::decode::<66, 66, 66>(&bytes).unwrap();
+
+    assert_eq!(decoded, buffer);
+}
+
+#[test]
+fn decode_rejects_mismatches() {
+    let mut buffer = PopBuffer::<2, UnorientedQuad>::new();
+    buffer.add_quad(
+        3,
+        UnorientedQuad {
+            minimum: UVec3::new(1, 2, 3),
+            width: 4,
+            height: 5,
+        },
+        1,
+    );
+    let bytes = buffer.encode::<18, 18, 18>();
+
+    assert_eq!(
+        PopBuffer::<2, UnorientedUnitQuad>::decode::<18, 18, 18>(&bytes),
+        Err(DecodeError::QuadKindMismatch {
+            expected: 0,
+            found: 2
+        })
+    );
+    assert!(matches!(
+        PopBuffer::<2, UnorientedQuad>::decode::<34, 34, 34>(&bytes),
+        Err(DecodeError::ShapeMismatch { .. })
+    ));
+    assert!(matches!(
+        PopBuffer::<3, UnorientedQuad>::decode::<18, 18, 18>(&bytes),
+        Err(DecodeError::LodCountMismatch { .. })
+    ));
+    assert_eq!(
+        PopBuffer::<2, UnorientedQuad>::decode::<18, 18, 18>(&bytes[..bytes.len() - 1]),
+        Err(DecodeError::UnexpectedEnd)
+    );
+}
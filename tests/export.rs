@@ -0,0 +1,78 @@
+mod common;
+
+use block_mesh_pop::{write_glb, write_obj, write_ply, ExportOptions, Mesher};
+
+use common::sphere;
+
+#[test]
+fn obj_has_a_group_per_lod() {
+    let voxels = sphere();
+    let mut mesher = Mesher::<18, 18, 18, 4>::new();
+    let buffer = mesher.mesh_visible_faces(&voxels);
+
+    let mut obj = Vec::new();
+    write_obj(buffer, &ExportOptions::default(), &mut obj).unwrap();
+    let obj = String::from_utf8(obj).unwrap();
+
+    let count = |prefix: &str| obj.lines().filter(|line| line.starts_with(prefix)).count();
+    assert_eq!(count("v "), buffer.num_quads() * 4);
+    assert_eq!(count("f "), buffer.num_quads() * 2);
+    assert_eq!(count("g lod"), 4);
+
+    let options = ExportOptions {
+        lod: Some(2),
+        ..Default::default()
+    };
+    let mut obj = Vec::new();
+    write_obj(buffer, &options, &mut obj).unwrap();
+    let obj = String::from_utf8(obj).unwrap();
+
+    let faces = obj.lines().filter(|line| line.starts_with("f ")).count();
+    assert_eq!(faces, buffer.to_lod_quads(2).num_quads() * 2);
+}
+
+#[test]
+fn ply_header_matches_data() {
+    let voxels = sphere();
+    let mut mesher = Mesher::<18, 18, 18, 4>::new();
+    let buffer = mesher.mesh_visible_faces(&voxels);
+
+    let mut ply = Vec::new();
+    write_ply(buffer, &ExportOptions::default(), &mut ply).unwrap();
+
+    let header_len = ply
+        .windows(11)
+        .position(|window| window == b"end_header\n")
+        .unwrap()
+        + 11;
+    let header = std::str::from_utf8(&ply[..header_len]).unwrap();
+    let quads = buffer.num_quads();
+
+    assert!(header.contains(&format!("element vertex {}\n", quads * 4)));
+    assert!(header.contains(&format!("element face {}\n", quads * 2)));
+    assert_eq!(ply.len() - header_len, quads * 4 * 24 + quads * 2 * 13);
+}
+
+#[test]
+fn glb_records_buckets() {
+    let voxels = sphere();
+    let mut mesher = Mesher::<18, 18, 18, 4>::new();
+    let buffer = mesher.mesh_visible_faces(&voxels);
+
+    let mut glb = Vec::new();
+    write_glb(buffer, &ExportOptions::default(), &mut glb).unwrap();
+
+    let read_u32 = |offset: usize| u32::from_le_bytes(glb[offset..offset + 4].try_into().unwrap());
+    assert_eq!(&glb[..4], b"glTF");
+    assert_eq!(read_u32(8) as usize, glb.len());
+
+    let json_len = read_u32(12) as usize;
+    assert_eq!(&glb[16..20], b"JSON");
+    let json = std::str::from_utf8(&glb[20..20 + json_len]).unwrap();
+    assert!(json.contains(&format!("\"buckets\":{:?}", buffer.get_buckets())));
+
+    let binary_len = read_u32(20 + json_len) as usize;
+    assert_eq!(&glb[24 + json_len..28 + json_len], b"BIN\0");
+    assert_eq!(binary_len, buffer.num_quads() * (4 * 24 + 6 * 4));
+    assert_eq!(glb.len(), 28 + json_len + binary_len);
+}
@@ -0,0 +1,89 @@
+use bevy_math::{IVec3, Vec3};
+use block_mesh_pop::{ChunkShape, Fbm, Mesher, Noise, NoiseKind, TerrainVoxel, WorldGenerator};
+
+const SHAPE: u32 = 18;
+
+#[test]
+fn noise_is_bounded_and_varies() {
+    for kind in [NoiseKind::Value, NoiseKind::Perlin, NoiseKind::Simplex] {
+        let positions =
+            (0..1000).map(|i| Vec3::new(i as f32 * 0.37, i as f32 * -1.3, i as f32 * 2.1));
+
+        // `Noise::sample` clamps to `-1.0..=1.0`, so a value of exactly one would be an
+        // overshoot hidden by the clamp.
+        let noise = Noise::new(kind, 7);
+        for position in positions.clone() {
+            assert!(
+                noise.sample(position * 0.1).abs() < 1.0,
+                "{kind:?} at {position}"
+            );
+        }
+
+        let noise = Fbm::new(noise, 3, 0.1);
+        let values: Vec<f32> = positions.map(|position| noise.sample(position)).collect();
+        assert!(values.iter().any(|&value| value < -0.1), "{kind:?}");
+        assert!(values.iter().any(|&value| value > 0.1), "{kind:?}");
+        assert_ne!(
+            Noise::new(kind, 1).sample(Vec3::new(0.3, 1.7, 2.2)),
+            Noise::new(kind, 2).sample(Vec3::new(0.3, 1.7, 2.2)),
+        );
+    }
+}
+
+#[test]
+fn chunks_are_deterministic_and_seamless() {
+    let generator = WorldGenerator::new(42);
+    let chunk = IVec3::new(-1, -1, 2);
+
+    let mut voxels = vec![TerrainVoxel::Empty; (SHAPE * SHAPE * SHAPE) as usize];
+    generator.fill_chunk::<SHAPE, SHAPE, SHAPE>(chunk, &mut voxels);
+
+    let other = std::thread::spawn(move || {
+        let mut voxels = vec![TerrainVoxel::Empty; (SHAPE * SHAPE * SHAPE) as usize];
+        WorldGenerator::new(42).fill_chunk::<SHAPE, SHAPE, SHAPE>(chunk, &mut voxels);
+        voxels
+    })
+    .join()
+    .unwrap();
+    assert_eq!(voxels, other);
+
+    // Caching the surface of each column doesn't change the voxels.
+    let mut expected = vec![TerrainVoxel::Empty; (SHAPE * SHAPE * SHAPE) as usize];
+    ChunkShape::<SHAPE, SHAPE, SHAPE>::fill_padded(chunk, &mut expected, |position| {
+        generator.get(position)
+    });
+    assert_eq!(voxels, expected);
+
+    assert!(voxels.contains(&TerrainVoxel::Empty));
+    assert!(voxels.iter().any(|voxel| *voxel != TerrainVoxel::Empty));
+}
+
+#[test]
+fn layers_follow_the_surface() {
+    let mut generator = WorldGenerator::new(3);
+    generator.caves = None;
+
+    let height = generator.surface_height(5, 9);
+    let material = |depth| generator.get(IVec3::new(5, height - 1 - depth, 9));
+
+    assert_eq!(generator.get(IVec3::new(5, height, 9)), TerrainVoxel::Empty);
+    assert_eq!(material(0), TerrainVoxel::Solid(1));
+    assert_eq!(material(1), TerrainVoxel::Solid(2));
+    assert_eq!(material(3), TerrainVoxel::Solid(2));
+    assert_eq!(material(4), TerrainVoxel::Solid(3));
+    assert_eq!(material(100), TerrainVoxel::Solid(3));
+}
+
+#[test]
+fn generated_chunks_can_be_meshed() {
+    let generator = WorldGenerator::new(1);
+    let mut voxels = vec![TerrainVoxel::Empty; (SHAPE * SHAPE * SHAPE) as usize];
+    let mut mesher = Mesher::<SHAPE, SHAPE, SHAPE, 4>::new();
+
+    let mut num_quads = 0;
+    for y in -4..4 {
+        generator.fill_chunk::<SHAPE, SHAPE, SHAPE>(IVec3::new(0, y, 0), &mut voxels);
+        num_quads += mesher.mesh_greedy(&voxels).num_quads();
+    }
+    assert!(num_quads > 0);
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use bevy_math::UVec3;
+use block_mesh_pop::{Heightmap, HeightmapTerrain, Mesher, PopBuffer, VisibleFacesQuads};
+
+use common::chunk;
+
+// The size of the chunks of `common`.
+const SHAPE: u32 = 18;
+
+fn heights() -> Vec<u32> {
+    (0..SHAPE * SHAPE)
+        .map(|i| {
+            let (x, z) = (i % SHAPE, i / SHAPE);
+            (x * 7 + z * 13 + x * z) % (SHAPE + 3)
+        })
+        .collect()
+}
+
+#[test]
+fn matches_visible_faces() {
+    let heights = heights();
+    let voxels = chunk(|position| {
+        let column = position.as_uvec3();
+        position.y < heights[(column.x + column.z * SHAPE) as usize] as f32
+    });
+
+    let mut mesher = Mesher::<SHAPE, SHAPE, SHAPE, 4>::new();
+    let mut expected = PopBuffer::new();
+    mesher.mesh_into::<VisibleFacesQuads, _>(&voxels, &mut expected);
+
+    assert!(expected.num_quads() > 0);
+    assert_eq!(*mesher.mesh_heightfield(&heights), expected);
+}
+
+#[test]
+fn meshes_terrain_chunks() {
+    let heights: Vec<u8> = (0..40 * 40)
+        .flat_map(|i: u32| ((i % 40 + i / 40) as u16 * 2).to_le_bytes())
+        .collect();
+    let terrain = HeightmapTerrain::new(
+        Heightmap::from_raw_le(&heights, 40).unwrap(),
+        u16::MAX as u32,
+    );
+
+    let mut mesher = Mesher::<SHAPE, SHAPE, SHAPE, 4>::new();
+    let mut columns = vec![0; (SHAPE * SHAPE) as usize];
+    let mut expected = PopBuffer::new();
+
+    // Above the bottom of the terrain, chunks mesh the same as their voxels.
+    for (chunk, voxels) in terrain.chunks::<SHAPE, SHAPE, SHAPE>() {
+        if chunk.y == 0 {
+            continue;
+        }
+
+        terrain.fill_heights::<SHAPE, SHAPE, SHAPE>(chunk, &mut columns);
+        mesher.mesh_into::<VisibleFacesQuads, _>(&voxels, &mut expected);
+        assert_eq!(*mesher.mesh_heightfield(&columns), expected, "{chunk}");
+    }
+
+    // The bottom padding is solid under the terrain, but not outside of it.
+    terrain.fill_heights::<SHAPE, SHAPE, SHAPE>(UVec3::ZERO, &mut columns);
+    assert_eq!(columns[0], 0);
+    assert_eq!(columns[(2 + SHAPE) as usize], 3);
+}
@@ -0,0 +1,96 @@
+use bevy::render::{
+    render_resource::{Extent3d, TextureDimension, TextureFormat},
+    texture::Image,
+};
+use bevy_math::{IVec3, UVec2, UVec3};
+use block_mesh_pop::{
+    ChunkShape, Heightmap, HeightmapError, HeightmapTerrain, MaterialMap, Mesher, TerrainVoxel,
+};
+
+const SHAPE: u32 = 10;
+const INNER: u32 = SHAPE - 2;
+
+fn terrain() -> HeightmapTerrain {
+    // A slope rising along x, from 1 to 20 voxels.
+    let heights: Vec<u16> = (0..20 * 12).map(|i| (i % 20 + 1) as u16).collect();
+    let bytes: Vec<u8> = heights.iter().flat_map(|h| h.to_le_bytes()).collect();
+
+    HeightmapTerrain::new(Heightmap::from_raw_le(&bytes, 20).unwrap(), u16::MAX as u32)
+}
+
+#[test]
+fn reads_raw_heights() {
+    let heightmap = Heightmap::from_raw_be(&[0x01, 0x02, 0x03, 0x04], 1).unwrap();
+    assert_eq!(heightmap.size, UVec2::new(1, 2));
+    assert_eq!(heightmap.heights, vec![0x0102, 0x0304]);
+
+    assert_eq!(
+        Heightmap::from_raw_le(&[0; 6], 2),
+        Err(HeightmapError::InvalidLength)
+    );
+}
+
+#[test]
+fn rejects_images_not_matching_their_size() {
+    let image = |width, height, data| {
+        let size = Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        Image::new(size, TextureDimension::D2, data, TextureFormat::R8Unorm)
+    };
+
+    let heightmap = Heightmap::from_image(&image(2, 1, vec![0, 255])).unwrap();
+    assert_eq!(heightmap.heights, vec![0, u16::MAX]);
+
+    assert_eq!(
+        Heightmap::from_image(&image(0, 0, Vec::new())),
+        Err(HeightmapError::InvalidLength)
+    );
+
+    let mut truncated = image(2, 2, vec![0; 4]);
+    truncated.data.truncate(3);
+    assert_eq!(
+        Heightmap::from_image(&truncated),
+        Err(HeightmapError::InvalidLength)
+    );
+}
+
+#[test]
+fn chunks_hold_the_voxels_of_their_neighbours() {
+    let terrain = terrain()
+        .with_materials(MaterialMap {
+            size: UVec2::new(20, 12),
+            materials: vec![3; 20 * 12],
+        })
+        .unwrap();
+
+    let chunks: Vec<_> = terrain.chunks::<SHAPE, SHAPE, SHAPE>().collect();
+    assert!(!chunks.is_empty());
+
+    for (chunk, voxels) in &chunks {
+        let minimum = (*chunk * INNER).as_ivec3() - IVec3::ONE;
+        for (index, voxel) in voxels.iter().enumerate() {
+            let local = ChunkShape::<SHAPE, SHAPE, SHAPE>::delinearize(index as u32);
+            assert_eq!(*voxel, terrain.get(minimum + local.as_ivec3()));
+        }
+    }
+
+    // The first chunk is neither buried nor above the slope.
+    assert_eq!(chunks[0].0, UVec3::ZERO);
+    assert_eq!(terrain.get(IVec3::new(0, 0, 0)), TerrainVoxel::Solid(3));
+    assert_eq!(terrain.get(IVec3::new(0, 1, 0)), TerrainVoxel::Empty);
+    assert_eq!(terrain.get(IVec3::new(-1, 0, 0)), TerrainVoxel::Empty);
+}
+
+#[test]
+fn chunks_can_be_meshed() {
+    let mut mesher = Mesher::<SHAPE, SHAPE, SHAPE, 3>::new();
+
+    let num_quads: usize = terrain()
+        .chunks::<SHAPE, SHAPE, SHAPE>()
+        .map(|(_, voxels)| mesher.mesh_greedy(&voxels).num_quads())
+        .sum();
+    assert!(num_quads > 0);
+}
@@ -0,0 +1,84 @@
+use bevy_math::UVec3;
+use block_mesh_pop::{build_lod_mesh, OrientedBlockFace, PopBuffer, UnorientedQuad};
+
+// The first face has its normal along X, U along Z and V along Y.
+const FACE: usize = 0;
+
+fn quad(z: u32, width: u32) -> UnorientedQuad {
+    UnorientedQuad {
+        minimum: UVec3::new(1, 1, z),
+        width,
+        height: 1,
+    }
+}
+
+#[test]
+fn quad_into_lod_covers_quad() {
+    let face = OrientedBlockFace::FACES[FACE];
+
+    assert_eq!(face.quad_into_lod(quad(2, 1), 0), quad(2, 1));
+    assert_eq!(
+        face.quad_into_lod(quad(2, 1), 1),
+        UnorientedQuad {
+            minimum: UVec3::new(1, 1, 1),
+            width: 2,
+            height: 2,
+        }
+    );
+    assert_eq!(
+        face.quad_into_lod(quad(2, 2), 1),
+        UnorientedQuad {
+            minimum: UVec3::new(1, 1, 1),
+            width: 4,
+            height: 2,
+        }
+    );
+}
+
+fn buffer() -> PopBuffer<2, UnorientedQuad> {
+    let mut buffer = PopBuffer::new();
+    buffer.add_quad(FACE, quad(5, 1), 0);
+    buffer.add_quad(FACE, quad(1, 1), 1);
+    buffer.add_quad(FACE, quad(2, 1), 1);
+    buffer.add_quad(FACE, quad(2, 2), 1);
+    buffer
+}
+
+#[test]
+fn to_lod_quads_deduplicates() {
+    let buffer = buffer();
+
+    let quads: Vec<_> = buffer.to_lod_quads(0).quads().map(|(_, &q)| q).collect();
+    assert_eq!(quads, [quad(1, 1), quad(2, 1), quad(2, 2), quad(5, 1)]);
+
+    // The first two snap onto the same quad, which the third partly overlaps, and the last one
+    // is not drawn at LOD 1.
+    let quads: Vec<_> = buffer.to_lod_quads(1).quads().map(|(_, &q)| q).collect();
+    assert_eq!(
+        quads,
+        [
+            UnorientedQuad {
+                minimum: UVec3::new(1, 1, 1),
+                width: 2,
+                height: 2,
+            },
+            UnorientedQuad {
+                minimum: UVec3::new(1, 1, 1),
+                width: 4,
+                height: 2,
+            },
+        ]
+    );
+}
+
+#[test]
+fn build_lod_mesh_matches_lod_quads() {
+    let buffer = buffer();
+
+    for (lod, quads) in [(0, 4), (1, 2)] {
+        let mesh = build_lod_mesh(&buffer, lod, 1.0);
+
+        assert_eq!(mesh.count_vertices(), quads * 4);
+        assert_eq!(mesh.indices().unwrap().len(), quads * 6);
+    }
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use block_mesh_pop::{
+    greedy_quads, visible_faces_quads, GreedyQuads, Mesher, PopBuffer, VisibleFacesQuads,
+    VisitedBuffer,
+};
+
+use common::{chunk, sphere, Voxel};
+
+/// A sphere and a slab, which share no quads.
+fn chunks() -> [Vec<Voxel>; 2] {
+    [sphere(), chunk(|position| position.y < 5.0)]
+}
+
+#[test]
+fn reused_mesher_matches_fresh_calls() {
+    let mut mesher = Mesher::<18, 18, 18, 4>::new();
+
+    for voxels in chunks() {
+        let mut expected = PopBuffer::new();
+        greedy_quads::<18, 18, 18, 4, _>(
+            &voxels,
+            &mut VisitedBuffer::new(voxels.len()),
+            &mut expected,
+        );
+        assert_eq!(*mesher.mesh_greedy(&voxels), expected);
+
+        let mut expected = PopBuffer::new();
+        visible_faces_quads::<18, 18, 18, 4, _>(
+            &voxels,
+            &mut VisitedBuffer::new(voxels.len()),
+            &mut expected,
+        );
+        assert_eq!(*mesher.mesh_visible_faces(&voxels), expected);
+    }
+}
+
+#[test]
+fn mesh_into_matches_fresh_calls() {
+    let mut mesher = Mesher::<18, 18, 18, 4>::new();
+    let mut quads = PopBuffer::new();
+    let mut unit_quads = PopBuffer::new();
+
+    for voxels in chunks() {
+        let mut expected = PopBuffer::new();
+        greedy_quads::<18, 18, 18, 4, _>(
+            &voxels,
+            &mut VisitedBuffer::new(voxels.len()),
+            &mut expected,
+        );
+        mesher.mesh_into::<GreedyQuads, _>(&voxels, &mut quads);
+        assert_eq!(quads, expected);
+
+        let mut expected = PopBuffer::new();
+        visible_faces_quads::<18, 18, 18, 4, _>(
+            &voxels,
+            &mut VisitedBuffer::new(voxels.len()),
+            &mut expected,
+        );
+        mesher.mesh_into::<VisibleFacesQuads, _>(&voxels, &mut unit_quads);
+        assert_eq!(unit_quads, expected);
+    }
+}
+
+#[test]
+fn closure_sink_counts_quads() {
+    for voxels in chunks() {
+        let mut buffer = PopBuffer::<4, _>::new();
+        greedy_quads::<18, 18, 18, 4, _>(
+            &voxels,
+            &mut VisitedBuffer::new(voxels.len()),
+            &mut buffer,
+        );
+
+        let mut count = 0;
+        greedy_quads::<18, 18, 18, 4, _>(
+            &voxels,
+            &mut VisitedBuffer::new(voxels.len()),
+            &mut |_, _, _| count += 1,
+        );
+
+        assert_eq!(count, buffer.num_quads());
+    }
+}
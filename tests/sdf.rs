@@ -0,0 +1,89 @@
+use bevy_math::{IVec3, Vec3};
+use block_mesh_pop::{
+    greedy_quads, PopBuffer, SdfNode, SdfSampling, SdfVoxelizer, TerrainVoxel, VisitedBuffer,
+};
+
+const SHAPE: u32 = 18;
+
+#[test]
+fn conservative_sampling_keeps_thin_features() {
+    // A sheet a tenth of a voxel thick, between voxel centres.
+    let sheet = |position: Vec3| (position.y - 4.0).abs() - 0.05;
+
+    let center = SdfVoxelizer {
+        sampling: SdfSampling::Center,
+        ..Default::default()
+    };
+    let conservative = SdfVoxelizer::default();
+
+    for x in 0..8 {
+        let position = IVec3::new(x, 3, 0);
+        assert_eq!(center.get(&sheet, position), TerrainVoxel::Empty);
+        assert_eq!(conservative.get(&sheet, position), TerrainVoxel::Solid(0));
+        assert_eq!(
+            conservative.get(&sheet, position + IVec3::Y * 2),
+            TerrainVoxel::Empty
+        );
+    }
+}
+
+#[test]
+fn primitives_keep_their_materials() {
+    let sdf = SdfNode::cuboid(Vec3::splat(4.0), Vec3::splat(4.0))
+        .with_material(1)
+        .union(SdfNode::sphere(Vec3::new(12.0, 4.0, 4.0), 3.0).with_material(2))
+        .difference(SdfNode::capsule(
+            Vec3::new(4.0, 0.0, 4.0),
+            Vec3::new(4.0, 8.0, 4.0),
+            1.5,
+        ));
+    let voxelizer = SdfVoxelizer {
+        sampling: SdfSampling::Center,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        voxelizer.get(&sdf, IVec3::new(1, 1, 1)),
+        TerrainVoxel::Solid(1)
+    );
+    assert_eq!(
+        voxelizer.get(&sdf, IVec3::new(12, 4, 4)),
+        TerrainVoxel::Solid(2)
+    );
+    assert_eq!(
+        voxelizer.get(&sdf, IVec3::new(4, 4, 4)),
+        TerrainVoxel::Empty
+    );
+    assert_eq!(
+        voxelizer.get(&sdf, IVec3::new(20, 4, 4)),
+        TerrainVoxel::Empty
+    );
+}
+
+#[test]
+fn chunks_cover_the_field() {
+    let sdf = SdfNode::torus(Vec3::ZERO, 12.0, 3.0).translate(Vec3::new(0.0, 2.0, 0.0));
+    let voxelizer = SdfVoxelizer {
+        voxel_size: 0.5,
+        ..Default::default()
+    };
+
+    let chunks: Vec<_> = voxelizer
+        .chunks::<SHAPE, SHAPE, SHAPE>(&sdf, Vec3::splat(-16.0), Vec3::splat(16.0))
+        .collect();
+    assert!(chunks.iter().any(|(chunk, _)| chunk.min_element() < 0));
+
+    let mut num_voxels = 0;
+    let mut visited = VisitedBuffer::new((SHAPE * SHAPE * SHAPE) as usize);
+    for (_, voxels) in &chunks {
+        num_voxels += voxels
+            .iter()
+            .filter(|voxel| **voxel != TerrainVoxel::Empty)
+            .count();
+
+        let mut buffer = PopBuffer::<4, _>::new();
+        greedy_quads::<SHAPE, SHAPE, SHAPE, 4, _>(voxels, &mut visited, &mut buffer);
+        assert!(buffer.num_quads() > 0);
+    }
+    assert!(num_voxels > 0);
+}
@@ -0,0 +1,40 @@
+use bevy_math::{IVec3, UVec3};
+use block_mesh_pop::ChunkShape;
+
+type Shape = ChunkShape<6, 5, 4>;
+
+#[test]
+fn padded_chunks_overlap_their_neighbours() {
+    let chunk = IVec3::new(1, -2, 3);
+    let mut positions = vec![IVec3::ZERO; 6 * 5 * 4];
+    Shape::fill_padded(chunk, &mut positions, |position| position);
+
+    let minimum = IVec3::new(4, -6, 6) - IVec3::ONE;
+    for (index, position) in positions.iter().enumerate() {
+        let local = Shape::delinearize(index as u32);
+        assert_eq!(*position, minimum + local.as_ivec3());
+    }
+
+    // The last inner voxel of a chunk is the padding of the next one.
+    let mut next = vec![IVec3::ZERO; 6 * 5 * 4];
+    Shape::fill_padded(chunk + IVec3::X, &mut next, |position| position);
+    assert_eq!(
+        positions[Shape::linearize(UVec3::new(4, 1, 1)) as usize],
+        next[Shape::linearize(UVec3::new(0, 1, 1)) as usize]
+    );
+}
+
+#[test]
+fn chunk_range_covers_positions() {
+    let chunks: Vec<_> = Shape::chunk_range(IVec3::new(-1, 0, 0), IVec3::new(4, 2, 1)).collect();
+    assert_eq!(
+        chunks,
+        [
+            IVec3::new(-1, 0, 0),
+            IVec3::new(0, 0, 0),
+            IVec3::new(1, 0, 0),
+        ]
+    );
+
+    assert_eq!(Shape::chunk_range(IVec3::ZERO, IVec3::splat(-1)).count(), 0);
+}
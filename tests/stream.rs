@@ -0,0 +1,79 @@
+mod common;
+
+use std::io;
+
+use block_mesh_pop::{DecodeError, Mesher, PopStreamDecoder, PopStreamReader, UnorientedUnitQuad};
+
+use common::sphere;
+
+#[test]
+fn reads_coarsest_lod_first() {
+    let voxels = sphere();
+    let mut mesher = Mesher::<18, 18, 18, 4>::new();
+    let buffer = mesher.mesh_visible_faces(&voxels);
+
+    let mut bytes = Vec::new();
+    buffer.write_stream::<18, 18, 18>(&mut bytes).unwrap();
+
+    let mut reader =
+        PopStreamReader::<_, 4, UnorientedUnitQuad>::new::<18, 18, 18>(bytes.as_slice());
+    for lod in (0..4).rev() {
+        assert_eq!(reader.read_lod().unwrap(), Some(lod));
+        assert_eq!(reader.decoder().loaded_lod(), Some(lod));
+        assert_eq!(
+            reader.decoder().buffer().num_quads_lod(lod),
+            buffer.num_quads_lod(lod)
+        );
+    }
+    assert_eq!(reader.read_lod().unwrap(), None);
+
+    let (rest, decoder) = reader.into_inner();
+    assert!(rest.is_empty());
+    assert_eq!(&decoder.into_buffer(), buffer);
+}
+
+#[test]
+fn decodes_bytes_as_they_arrive() {
+    let voxels = sphere();
+    let mut mesher = Mesher::<18, 18, 18, 4>::new();
+    let buffer = mesher.mesh_visible_faces(&voxels);
+
+    let mut bytes = Vec::new();
+    buffer.write_stream::<18, 18, 18>(&mut bytes).unwrap();
+
+    let mut decoder = PopStreamDecoder::<4, UnorientedUnitQuad>::new::<18, 18, 18>();
+    let mut loaded = Vec::new();
+    for byte in bytes {
+        decoder.push(&[byte]);
+        while let Some(lod) = decoder.decode_lod().unwrap() {
+            loaded.push(lod);
+        }
+    }
+
+    assert_eq!(loaded, [3, 2, 1, 0]);
+    assert!(decoder.is_complete());
+    assert_eq!(&decoder.into_buffer(), buffer);
+}
+
+#[test]
+fn rejects_more_quads_than_faces() {
+    let voxels = sphere();
+    let mut mesher = Mesher::<18, 18, 18, 4>::new();
+    let buffer = mesher.mesh_visible_faces(&voxels);
+
+    let mut bytes = Vec::new();
+    buffer.write_stream::<18, 18, 18>(&mut bytes).unwrap();
+
+    // The counts of the coarsest LOD follow the header.
+    let header_len = 4 + 2 + 3 * 4 + 1;
+    bytes[header_len..header_len + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+
+    let mut decoder = PopStreamDecoder::<4, UnorientedUnitQuad>::new::<18, 18, 18>();
+    decoder.push(&bytes);
+    assert_eq!(decoder.decode_lod(), Err(DecodeError::TooManyQuads));
+
+    let mut reader =
+        PopStreamReader::<_, 4, UnorientedUnitQuad>::new::<18, 18, 18>(bytes.as_slice());
+    let error = reader.read_lod().unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+}
@@ -0,0 +1,154 @@
+use bevy::render::{color::Color, mesh::Mesh, mesh::VertexAttributeValues};
+use bevy_math::{IVec3, UVec3};
+use block_mesh_pop::{
+    build_vox_mesh, greedy_quads, ChunkShape, Mesher, PopBuffer, VisitedBuffer, VoxError, VoxScene,
+    VoxVoxel,
+};
+
+fn chunk(id: &[u8; 4], content: &[u8], children: &[u8]) -> Vec<u8> {
+    let mut bytes = id.to_vec();
+    bytes.extend_from_slice(&(content.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&(children.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(content);
+    bytes.extend_from_slice(children);
+    bytes
+}
+
+fn string(bytes: &mut Vec<u8>, value: &str) {
+    bytes.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(value.as_bytes());
+}
+
+fn ints(values: &[i32]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect()
+}
+
+fn model(size: [u32; 3], voxels: &[[u8; 4]]) -> Vec<u8> {
+    let mut bytes = chunk(b"SIZE", &ints(&size.map(|x| x as i32)), &[]);
+    let mut xyzi = ints(&[voxels.len() as i32]);
+    xyzi.extend(voxels.iter().flatten());
+    bytes.extend(chunk(b"XYZI", &xyzi, &[]));
+    bytes
+}
+
+fn transform(id: i32, child: i32, translation: Option<&str>) -> Vec<u8> {
+    let mut content = ints(&[id, 0, child, -1, 0, 1]);
+    match translation {
+        Some(translation) => {
+            content.extend(ints(&[1]));
+            string(&mut content, "_t");
+            string(&mut content, translation);
+        }
+        None => content.extend(ints(&[0])),
+    }
+    chunk(b"nTRN", &content, &[])
+}
+
+fn file(children: &[u8]) -> Vec<u8> {
+    let mut bytes = b"VOX ".to_vec();
+    bytes.extend(ints(&[150]));
+    bytes.extend(chunk(b"MAIN", &[], children));
+    bytes
+}
+
+#[test]
+fn parses_single_model() {
+    let mut children = model([2, 3, 4], &[[0, 0, 0, 1], [1, 2, 3, 7]]);
+    let mut rgba = vec![0; 1024];
+    rgba[24..28].copy_from_slice(&[10, 20, 30, 255]);
+    children.extend(chunk(b"RGBA", &rgba, &[]));
+
+    let scene = VoxScene::parse(&file(&children)).unwrap();
+
+    assert_eq!(scene.models.len(), 1);
+    let model = &scene.models[0];
+    assert_eq!(model.size, UVec3::new(2, 3, 4));
+    assert_eq!(model.get(UVec3::ZERO), VoxVoxel(1));
+    assert_eq!(model.get(UVec3::new(1, 2, 3)), VoxVoxel(7));
+    assert_eq!(model.get(UVec3::new(1, 1, 1)), VoxVoxel::EMPTY);
+    assert_eq!(VoxVoxel(7).color(&scene.palette), [10, 20, 30, 255]);
+
+    assert_eq!(scene.instances.len(), 1);
+    assert_eq!(scene.merge(), *model);
+}
+
+#[test]
+fn rejects_oversized_models() {
+    let children = model([256, 1, 65536], &[]);
+
+    assert_eq!(
+        VoxScene::parse(&file(&children)),
+        Err(VoxError::InvalidSize(UVec3::new(256, 1, 65536)))
+    );
+}
+
+#[test]
+fn places_models_of_scene_graph() {
+    let mut children = model([1, 1, 1], &[[0, 0, 0, 1]]);
+    children.extend(model([1, 1, 1], &[[0, 0, 0, 2]]));
+    children.extend(transform(0, 1, None));
+    children.extend(chunk(b"nGRP", &ints(&[1, 0, 2, 2, 4]), &[]));
+    children.extend(transform(2, 3, Some("0 0 0")));
+    children.extend(chunk(b"nSHP", &ints(&[3, 0, 1, 0, 0]), &[]));
+    children.extend(transform(4, 5, Some("3 0 -1")));
+    children.extend(chunk(b"nSHP", &ints(&[5, 0, 1, 1, 0]), &[]));
+
+    let scene = VoxScene::parse(&file(&children)).unwrap();
+
+    assert_eq!(scene.instances.len(), 2);
+    assert_eq!(scene.instances[1].translation, IVec3::new(3, 0, -1));
+
+    let merged = scene.merge();
+    assert_eq!(merged.size, UVec3::new(4, 1, 2));
+    assert_eq!(merged.get(UVec3::new(0, 0, 1)), VoxVoxel(1));
+    assert_eq!(merged.get(UVec3::new(3, 0, 0)), VoxVoxel(2));
+}
+
+#[test]
+fn splits_into_padded_chunks() {
+    let voxels: Vec<_> = (0..20u8).map(|x| [x, 0, 0, 1]).collect();
+    let scene = VoxScene::parse(&file(&model([20, 1, 1], &voxels))).unwrap();
+
+    let chunks: Vec<_> = scene.models[0].chunks::<18, 18, 18>().collect();
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(chunks[1].0, UVec3::new(1, 0, 0));
+
+    let mut visited = VisitedBuffer::new(18 * 18 * 18);
+    let mut buffer = PopBuffer::<1, _>::new();
+    for (_, voxels) in &chunks {
+        greedy_quads::<18, 18, 18, 1, _>(voxels, &mut visited, &mut buffer);
+    }
+
+    // The end between the chunks is hidden by the padding.
+    assert_eq!(buffer.num_quads(), 2 * 5);
+}
+
+#[test]
+fn vox_mesh_colors_match_quads() {
+    let voxels: Vec<_> = (0..6u8).map(|x| [x, 0, x % 2, 1 + x % 3]).collect();
+    let scene = VoxScene::parse(&file(&model([6, 1, 2], &voxels))).unwrap();
+    let (_, voxels) = scene.models[0].chunks::<18, 18, 18>().next().unwrap();
+
+    let mut mesher = Mesher::<18, 18, 18, 2>::new();
+    let buffer = mesher.mesh_greedy(&voxels);
+    let (_, mesh) = build_vox_mesh::<18, 18, 18, 2, _>(buffer, &voxels, &scene.palette);
+
+    let Some(VertexAttributeValues::Float32x4(colors)) = mesh.attribute(Mesh::ATTRIBUTE_COLOR)
+    else {
+        panic!("mesh has no vertex colours");
+    };
+    assert_eq!(colors.len(), mesh.count_vertices());
+
+    for ((_, quad), colors) in buffer.quads().zip(colors.chunks(4)) {
+        let minimum = quad.minimum;
+        let voxel = voxels[ChunkShape::<18, 18, 18>::linearize(minimum) as usize];
+        assert_ne!(voxel, VoxVoxel::EMPTY);
+
+        let [r, g, b, a] = voxel.color(&scene.palette);
+        let color = Color::rgba_u8(r, g, b, a).as_linear_rgba_f32();
+        assert!(colors.iter().all(|&vertex| vertex == color));
+    }
+}
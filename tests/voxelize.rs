@@ -0,0 +1,123 @@
+use bevy::prelude::{shape, Mesh};
+use bevy_math::IVec3;
+use block_mesh_pop::{
+    MeshVoxelization, MeshVoxelizer, Mesher, TerrainVoxel, TriangleMesh, TriangleMeshError,
+};
+
+const SHAPE: u32 = 10;
+
+const CUBE: &str = "\
+# A cube from 0.1 to 1.9
+v 0.1 0.1 0.1
+v 1.9 0.1 0.1
+v 1.9 1.9 0.1
+v 0.1 1.9 0.1
+v 0.1 0.1 1.9
+v 1.9 0.1 1.9
+v 1.9 1.9 1.9
+v 0.1 1.9 1.9
+f 1 4 3 2
+f 5 6 7 8
+f 1 2 6 5
+f 4 8 7 3
+f 1 5 8 4
+f 2/1 3/2 7/3 6/4
+";
+
+fn count(voxelizer: MeshVoxelizer, mesh: &TriangleMesh) -> usize {
+    voxelizer
+        .voxelize(mesh)
+        .unwrap()
+        .voxels
+        .iter()
+        .filter(|voxel| **voxel != TerrainVoxel::Empty)
+        .count()
+}
+
+#[test]
+fn parses_obj() {
+    let mesh = TriangleMesh::parse_obj(CUBE).unwrap();
+    assert_eq!(mesh.positions.len(), 8);
+    assert_eq!(mesh.indices.len(), 6 * 2 * 3);
+
+    assert_eq!(
+        TriangleMesh::parse_obj("v 0 0 0\nf 1 2 3\n"),
+        Err(TriangleMeshError::InvalidObj { line: 2 })
+    );
+}
+
+#[test]
+fn voxelizes_surface_and_solid() {
+    let mesh = TriangleMesh::parse_obj(CUBE).unwrap();
+    let surface = MeshVoxelizer {
+        voxel_size: 0.5,
+        material: 4,
+        ..Default::default()
+    };
+    let solid = MeshVoxelizer {
+        mode: MeshVoxelization::Solid,
+        ..surface
+    };
+
+    // The cube covers 4 voxels on each side, of which the inner 2 aren't on its surface.
+    assert_eq!(count(surface, &mesh), 4 * 4 * 4 - 2 * 2 * 2);
+    assert_eq!(count(solid, &mesh), 4 * 4 * 4);
+
+    let voxels = solid.voxelize(&mesh).unwrap();
+    assert_eq!(voxels.get(IVec3::new(1, 2, 1)), TerrainVoxel::Solid(4));
+    assert_eq!(voxels.get(IVec3::new(4, 2, 1)), TerrainVoxel::Empty);
+}
+
+#[test]
+fn voxelizes_bevy_meshes_into_chunks() {
+    let mesh = Mesh::from(shape::UVSphere {
+        radius: 10.0,
+        sectors: 32,
+        stacks: 16,
+    });
+    let mesh = TriangleMesh::from_mesh(&mesh).unwrap();
+    let voxels = MeshVoxelizer {
+        mode: MeshVoxelization::Solid,
+        ..Default::default()
+    }
+    .voxelize(&mesh)
+    .unwrap();
+
+    assert_eq!(voxels.get(IVec3::ZERO), TerrainVoxel::Solid(0));
+    assert_eq!(voxels.get(IVec3::splat(9)), TerrainVoxel::Empty);
+
+    let mut mesher = Mesher::<SHAPE, SHAPE, SHAPE, 3>::new();
+    let mut num_chunks = 0;
+    for (_, chunk_voxels) in voxels.chunks::<SHAPE, SHAPE, SHAPE>() {
+        assert!(mesher.mesh_greedy(&chunk_voxels).num_quads() > 0);
+        num_chunks += 1;
+    }
+    assert!(num_chunks >= 8);
+}
+
+#[test]
+fn rejects_non_finite_and_oversized_meshes() {
+    let voxelizer = MeshVoxelizer::default();
+    let voxelize = |obj: &str| voxelizer.voxelize(&TriangleMesh::parse_obj(obj).unwrap());
+
+    assert_eq!(
+        voxelize("v inf 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n"),
+        Err(TriangleMeshError::NonFinitePosition)
+    );
+
+    // A 2 km prop at 10 cm voxels.
+    let prop = MeshVoxelizer {
+        voxel_size: 0.1,
+        ..voxelizer
+    };
+    assert_eq!(
+        prop.voxelize(
+            &TriangleMesh::parse_obj("v 0 0 0\nv 2000 0 0\nv 0 2000 2000\nf 1 2 3\n").unwrap()
+        ),
+        Err(TriangleMeshError::TooLarge)
+    );
+    assert_eq!(
+        voxelize("v 1e10 0 0\nv 1e10 1 0\nv 1e10 0 1\nf 1 2 3\n"),
+        Err(TriangleMeshError::TooLarge)
+    );
+}